@@ -0,0 +1,83 @@
+//! Print the spans emitted for each request when the `tracing` feature is
+//! enabled.
+//!
+//! Run with `cargo run --example tracing --features tracing [-- <esplora url>]`.
+//!
+//! A real application would rather install a subscriber from the
+//! `tracing-subscriber` crate, this one only prints the fields of the spans.
+
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use esplora_client::Builder;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Subscriber printing the spans of `esplora_client` as they are created and
+/// as their fields are recorded.
+#[derive(Default)]
+struct PrintSpans {
+    next_id: AtomicU64,
+}
+
+/// Formats the visited fields as `name=value` pairs.
+#[derive(Default)]
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let _ = write!(self.0, " {}={:?}", field.name(), value);
+    }
+}
+
+impl Subscriber for PrintSpans {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        // Leave out the spans of the HTTP client's own dependencies.
+        metadata.target().starts_with("esplora_client")
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let id = Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let mut fields = Fields::default();
+        span.record(&mut fields);
+        println!(
+            "new span {} {}:{}",
+            id.into_u64(),
+            span.metadata().name(),
+            fields.0
+        );
+        id
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        println!("record span {}:{}", span.into_u64(), fields.0);
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[tokio::main]
+async fn main() -> Result<(), esplora_client::Error> {
+    tracing::subscriber::set_global_default(PrintSpans::default())
+        .expect("no other subscriber is set");
+
+    let url = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "https://blockstream.info/api".to_string());
+    let client = Builder::new(&url).build_async()?;
+
+    let height = client.get_height().await?;
+    let hash = client.get_block_hash(height).await?;
+    println!("tip is block {hash} at height {height}");
+
+    Ok(())
+}
@@ -1,42 +1,58 @@
 //! Structs from the Esplora API
 //!
+//! The structs parse the responses of both Blockstream's esplora and
+//! mempool.space: fields not returned by every backend are either `Option`s or
+//! default when missing, and unknown fields are ignored.
+//!
+//! The summaries and statistics returned by the server are `#[non_exhaustive]`,
+//! so that fields can be added as backends return more of them. The structs
+//! describing transactions can still be built, e.g. to test code using them.
+//!
 //! See: <https://github.com/Blockstream/esplora/blob/master/API.md>
 
 pub use bitcoin::consensus::{deserialize, serialize};
+use bitcoin::hashes::{sha256d, Hash, HashEngine};
+use bitcoin::hex::DisplayHex;
 pub use bitcoin::hex::FromHex;
-use bitcoin::Weight;
 pub use bitcoin::{
     transaction, Amount, BlockHash, OutPoint, ScriptBuf, Transaction, TxIn, TxOut, Txid, Witness,
 };
+use bitcoin::{TxMerkleNode, Weight};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct PrevOut {
     pub value: u64,
     pub scriptpubkey: ScriptBuf,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Vin {
     pub txid: Txid,
     pub vout: u32,
     // None if coinbase
     pub prevout: Option<PrevOut>,
+    #[serde(default)]
     pub scriptsig: ScriptBuf,
-    #[serde(deserialize_with = "deserialize_witness", default)]
+    #[serde(
+        serialize_with = "serialize_witness",
+        deserialize_with = "deserialize_witness",
+        default
+    )]
     pub witness: Vec<Vec<u8>>,
     pub sequence: u32,
+    #[serde(default)]
     pub is_coinbase: bool,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Vout {
     pub value: u64,
     pub scriptpubkey: ScriptBuf,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct TxStatus {
     pub confirmed: bool,
     pub block_height: Option<u32>,
@@ -44,14 +60,30 @@ pub struct TxStatus {
     pub block_time: Option<u64>,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UtxoStatus {
+    pub confirmed: bool,
+    pub block_height: Option<u32>,
+    pub block_hash: Option<BlockHash>,
+    pub block_time: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Utxo {
+    pub txid: Txid,
+    pub vout: u32,
+    pub status: UtxoStatus,
+    pub value: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct MerkleProof {
     pub block_height: u32,
     pub merkle: Vec<Txid>,
     pub pos: usize,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct OutputStatus {
     pub spent: bool,
     pub txid: Option<Txid>,
@@ -59,14 +91,14 @@ pub struct OutputStatus {
     pub status: Option<TxStatus>,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct BlockStatus {
     pub in_best_chain: bool,
     pub height: Option<u32>,
     pub next_best: Option<BlockHash>,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Tx {
     pub txid: Txid,
     pub version: i32,
@@ -81,13 +113,24 @@ pub struct Tx {
     pub fee: u64,
 }
 
-#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+/// A transaction along with the spending status of each of its outputs, e.g.
+/// for an explorer to show both where its coins come from and where they went.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TxWithSpends {
+    /// The transaction, including the previous output of each input.
+    pub tx: Tx,
+    /// The spending status of each output of the transaction, in output order.
+    pub outspends: Vec<OutputStatus>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct BlockTime {
     pub timestamp: u64,
     pub height: u32,
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct BlockSummary {
     pub id: BlockHash,
     #[serde(flatten)]
@@ -97,8 +140,47 @@ pub struct BlockSummary {
     pub merkle_root: bitcoin::hash_types::TxMerkleNode,
 }
 
+/// Information about a block, from the `/block/:hash` endpoint.
+///
+/// The backends differ in the fields they return: Blockstream's esplora
+/// returns all of them, while older esplora versions and some other backends
+/// omit `mediantime` and `difficulty`, which are then `None`. Neither
+/// Blockstream's esplora nor mempool.space return the chainwork.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct BlockInfo {
+    /// The block hash.
+    pub id: BlockHash,
+    /// The block height.
+    pub height: u32,
+    /// The block version.
+    pub version: i32,
+    /// The block timestamp, in seconds since the unix epoch.
+    pub timestamp: u64,
+    /// The number of transactions in the block.
+    pub tx_count: u64,
+    /// The block size, in bytes.
+    pub size: usize,
+    /// The block weight, in weight units.
+    pub weight: u64,
+    /// The merkle root of the block's transactions.
+    pub merkle_root: TxMerkleNode,
+    /// Hash of the previous block, `None` for the genesis block.
+    pub previousblockhash: Option<BlockHash>,
+    /// The median timestamp of the previous 11 blocks, if returned by the
+    /// backend.
+    pub mediantime: Option<u64>,
+    /// The block nonce.
+    pub nonce: u32,
+    /// The compact encoding of the block's target.
+    pub bits: u32,
+    /// The block difficulty, if returned by the backend.
+    pub difficulty: Option<f64>,
+}
+
 /// Address statistics, includes the address, and the utxo information for the address.
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct AddressStats {
     /// The address.
     pub address: String,
@@ -109,7 +191,8 @@ pub struct AddressStats {
 }
 
 /// Contains a summary of the transactions for an address.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct AddressTxsSummary {
     /// The number of funded transaction outputs.
     pub funded_txo_count: u32,
@@ -123,6 +206,337 @@ pub struct AddressTxsSummary {
     pub tx_count: u32,
 }
 
+/// Statistics about a particular script hash's confirmed and mempool transactions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ScriptHashStats {
+    /// The summary of transactions for this script hash, already on chain.
+    pub chain_stats: ScriptHashTxsSummary,
+    /// The summary of transactions for this script hash, currently in the mempool.
+    pub mempool_stats: ScriptHashTxsSummary,
+}
+
+/// Contains a summary of the transactions for a particular script hash.
+pub type ScriptHashTxsSummary = AddressTxsSummary;
+
+/// Statistics about the mempool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct MempoolStats {
+    /// The number of transactions in the mempool.
+    pub count: usize,
+    /// The total size of mempool transactions in virtual bytes.
+    pub vsize: usize,
+    /// The total fees paid by mempool transactions, in satoshis.
+    pub total_fee: u64,
+    /// The mempool's fee rate distribution histogram.
+    ///
+    /// An array of `(feerate, vsize)` tuples, where each entry's `vsize` is the total vsize of
+    /// transactions paying more than `feerate` but less than the previous entry's `feerate`
+    /// (except for the first entry, which has no upper bound). Empty if
+    /// missing from the response.
+    #[serde(default)]
+    pub fee_histogram: Vec<(f64, usize)>,
+}
+
+/// A transaction that recently entered the mempool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct MempoolRecentTx {
+    /// The transaction id.
+    pub txid: Txid,
+    /// The fee paid by the transaction, in satoshis.
+    pub fee: u64,
+    /// The transaction size, in virtual bytes.
+    pub vsize: usize,
+    /// The combined value of the transaction outputs, in satoshis.
+    pub value: u64,
+}
+
+/// Progress of the current difficulty adjustment period, as returned by a
+/// mempool.space backend.
+///
+/// Times are in milliseconds, except for `previous_time`.
+#[cfg(feature = "mempool-space")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct DifficultyAdjustment {
+    /// How far into the current period the chain is, in percent.
+    pub progress_percent: f64,
+    /// The estimated change of the difficulty at the next retarget, in
+    /// percent.
+    pub difficulty_change: f64,
+    /// The estimated time of the next retarget, in milliseconds since the
+    /// unix epoch.
+    pub estimated_retarget_date: u64,
+    /// The number of blocks left until the next retarget.
+    pub remaining_blocks: u32,
+    /// The estimated time left until the next retarget.
+    pub remaining_time: u64,
+    /// The change of the difficulty at the previous retarget, in percent.
+    pub previous_retarget: f64,
+    /// The timestamp of the block of the previous retarget, in seconds since
+    /// the unix epoch, if returned by the backend.
+    #[serde(default)]
+    pub previous_time: Option<u64>,
+    /// The height of the next retarget.
+    pub next_retarget_height: u32,
+    /// The average time between the blocks of the current period.
+    pub time_avg: u64,
+    /// The average time between blocks adjusted for the time offset, if
+    /// returned by the backend.
+    #[serde(default)]
+    pub adjusted_time_avg: Option<u64>,
+    /// The offset applied on testnet, where difficulty drops after 20
+    /// minutes without a block, if returned by the backend.
+    #[serde(default)]
+    pub time_offset: Option<i64>,
+    /// The number of blocks expected to have been mined by now in the current
+    /// period, if returned by the backend.
+    #[serde(default)]
+    pub expected_blocks: Option<f64>,
+}
+
+/// Feerates recommended by a mempool.space backend, in sat/vB, for how soon
+/// a transaction should confirm.
+///
+/// Unlike the fee estimates of `/fee-estimates`, which are keyed by
+/// confirmation target, these are computed from the projected next blocks of
+/// the backend's mempool.
+#[cfg(feature = "mempool-space")]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct RecommendedFees {
+    /// Feerate to confirm in the next block.
+    pub fastest_fee: f64,
+    /// Feerate to confirm within half an hour, i.e. 3 blocks.
+    pub half_hour_fee: f64,
+    /// Feerate to confirm within an hour, i.e. 6 blocks.
+    pub hour_fee: f64,
+    /// Feerate to confirm eventually, without any time preference.
+    pub economy_fee: f64,
+    /// Minimum feerate for a transaction to be accepted in the mempool.
+    pub minimum_fee: f64,
+}
+
+/// Information about a Liquid asset, as returned by a Liquid Esplora backend.
+///
+/// The native asset (L-BTC) is neither issued nor registered: it has no
+/// issuance fields and its statistics count peg-ins and peg-outs instead.
+#[cfg(feature = "elements")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct AssetInfo {
+    /// The asset id, in hex.
+    pub asset_id: String,
+    /// The transaction input which issued the asset.
+    pub issuance_txin: Option<AssetIssuanceTxIn>,
+    /// The output spent by the input which issued the asset.
+    pub issuance_prevout: Option<AssetIssuancePrevOut>,
+    /// The id of the token allowing to reissue the asset, in hex.
+    pub reissuance_token: Option<String>,
+    /// The hash of the contract committed to by the issuance, in hex.
+    pub contract_hash: Option<String>,
+    /// The confirmation status of the issuance transaction.
+    pub status: Option<TxStatus>,
+    /// The statistics of the asset's confirmed transactions.
+    pub chain_stats: AssetStats,
+    /// The statistics of the asset's transactions in the mempool.
+    pub mempool_stats: AssetStats,
+    /// The name of the asset, if registered.
+    pub name: Option<String>,
+    /// The ticker of the asset, if registered.
+    pub ticker: Option<String>,
+    /// The number of decimal places of the asset, if registered.
+    pub precision: Option<u8>,
+}
+
+/// The transaction input which issued an asset.
+#[cfg(feature = "elements")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetIssuanceTxIn {
+    /// The id of the issuance transaction.
+    pub txid: Txid,
+    /// The index of the input within the issuance transaction.
+    pub vin: u32,
+}
+
+/// The output spent by the input which issued an asset.
+#[cfg(feature = "elements")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetIssuancePrevOut {
+    /// The id of the transaction holding the output.
+    pub txid: Txid,
+    /// The index of the output within the transaction.
+    pub vout: u32,
+}
+
+/// Statistics about an asset's transactions.
+///
+/// The issuance fields are zero for the native asset, and the peg fields are
+/// zero for any other asset. Amounts are in the asset's base units.
+#[cfg(feature = "elements")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct AssetStats {
+    /// The number of transactions involving the asset.
+    pub tx_count: u64,
+    /// The number of issuances, including reissuances.
+    pub issuance_count: u64,
+    /// The amount issued, excluding blinded issuances.
+    pub issued_amount: u64,
+    /// The amount burned.
+    pub burned_amount: u64,
+    /// Whether some issuances have a blinded amount.
+    pub has_blinded_issuances: bool,
+    /// The number of reissuance tokens, `None` if blinded.
+    pub reissuance_tokens: Option<u64>,
+    /// The number of reissuance tokens burned.
+    pub burned_reissuance_tokens: u64,
+    /// The number of peg-ins, for the native asset.
+    pub peg_in_count: u64,
+    /// The amount pegged in, for the native asset.
+    pub peg_in_amount: u64,
+    /// The number of peg-outs, for the native asset.
+    pub peg_out_count: u64,
+    /// The amount pegged out, for the native asset.
+    pub peg_out_amount: u64,
+    /// The number of burn outputs, for the native asset.
+    pub burn_count: u64,
+}
+
+/// A Liquid transaction involving an asset.
+///
+/// Unlike [`Tx`] its inputs and outputs may be confidential, in which case
+/// only the commitments to their asset and value are known.
+#[cfg(feature = "elements")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetTx {
+    pub txid: Txid,
+    pub version: i32,
+    pub locktime: u32,
+    pub vin: Vec<AssetVin>,
+    pub vout: Vec<AssetVout>,
+    /// Transaction size in raw bytes (NOT virtual bytes).
+    pub size: usize,
+    /// Transaction weight units.
+    pub weight: u64,
+    pub status: TxStatus,
+    /// The fee paid in the native asset, as set by the transaction's fee output.
+    pub fee: u64,
+}
+
+/// An input of an [`AssetTx`].
+#[cfg(feature = "elements")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetVin {
+    pub txid: Txid,
+    pub vout: u32,
+    /// None if coinbase or peg-in.
+    pub prevout: Option<AssetVout>,
+    #[serde(default)]
+    pub is_coinbase: bool,
+    /// Whether the input claims coins pegged in from the parent chain.
+    #[serde(default)]
+    pub is_pegin: bool,
+    pub sequence: u32,
+}
+
+/// An output of an [`AssetTx`].
+///
+/// Exactly one of `value` and `valuecommitment` is set, and likewise for
+/// `asset` and `assetcommitment`.
+#[cfg(feature = "elements")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetVout {
+    pub scriptpubkey: ScriptBuf,
+    /// The value, in the asset's base units, if not confidential.
+    pub value: Option<u64>,
+    /// The commitment to the value, in hex, if confidential.
+    pub valuecommitment: Option<String>,
+    /// The asset id, in hex, if not confidential.
+    pub asset: Option<String>,
+    /// The commitment to the asset, in hex, if confidential.
+    pub assetcommitment: Option<String>,
+}
+
+impl MerkleProof {
+    /// Check that the proof commits `txid` to the given merkle root, such as
+    /// the `merkle_root` of the header of the block at `block_height`.
+    ///
+    /// The root is recomputed by hashing `txid` with each node of the `merkle`
+    /// path in turn, on the side given by the bits of `pos`.
+    pub fn verify(&self, txid: &Txid, merkle_root: &TxMerkleNode) -> bool {
+        let mut pos = self.pos;
+        let mut node = txid.to_raw_hash();
+        for sibling in &self.merkle {
+            let mut engine = sha256d::Hash::engine();
+            if pos & 1 == 0 {
+                engine.input(node.as_byte_array());
+                engine.input(sibling.as_byte_array());
+            } else {
+                engine.input(sibling.as_byte_array());
+                engine.input(node.as_byte_array());
+            }
+            node = sha256d::Hash::from_engine(engine);
+            pos >>= 1;
+        }
+        // The position can't be deeper than the path.
+        pos == 0 && TxMerkleNode::from_raw_hash(node) == *merkle_root
+    }
+}
+
+impl TxStatus {
+    /// Get the number of confirmations of the transaction given the height of
+    /// the current blockchain tip, `0` if it is unconfirmed.
+    pub fn confirmations(&self, tip_height: u32) -> u32 {
+        match self.block_height {
+            Some(block_height) if self.confirmed => {
+                tip_height.saturating_add(1).saturating_sub(block_height)
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl AddressTxsSummary {
+    /// Get the balance, in satoshis, as the sum of the funded outputs minus
+    /// the sum of the spent ones.
+    ///
+    /// This is negative for mempool stats spending outputs that were funded
+    /// on chain.
+    pub fn balance(&self) -> i64 {
+        (self.funded_txo_sum as i64).saturating_sub(self.spent_txo_sum as i64)
+    }
+}
+
+impl From<BlockInfo> for BlockSummary {
+    fn from(info: BlockInfo) -> Self {
+        BlockSummary {
+            id: info.id,
+            time: BlockTime {
+                timestamp: info.timestamp,
+                height: info.height,
+            },
+            previousblockhash: info.previousblockhash,
+            merkle_root: info.merkle_root,
+        }
+    }
+}
+
+impl AddressStats {
+    /// Get the balance, in satoshis, including both the confirmed
+    /// transactions and those in the mempool.
+    pub fn total_balance(&self) -> i64 {
+        self.chain_stats
+            .balance()
+            .saturating_add(self.mempool_stats.balance())
+    }
+}
+
 impl Tx {
     pub fn to_tx(&self) -> Transaction {
         Transaction {
@@ -188,6 +602,13 @@ impl Tx {
     }
 }
 
+fn serialize_witness<S>(witness: &[Vec<u8>], s: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::ser::Serializer,
+{
+    s.collect_seq(witness.iter().map(|item| item.to_lower_hex_string()))
+}
+
 fn deserialize_witness<'de, D>(d: D) -> Result<Vec<Vec<u8>>, D::Error>
 where
     D: serde::de::Deserializer<'de>,
@@ -11,37 +11,86 @@
 
 //! Esplora by way of `reqwest` HTTP client.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::marker::PhantomData;
+use std::pin::Pin;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
 use bitcoin::consensus::{deserialize, serialize, Decodable, Encodable};
-use bitcoin::hashes::{sha256, Hash};
+use bitcoin::hashes::sha256;
 use bitcoin::hex::{DisplayHex, FromHex};
-use bitcoin::Address;
 use bitcoin::{
-    block::Header as BlockHeader, Block, BlockHash, MerkleBlock, Script, Transaction, Txid,
+    block::Header as BlockHeader, Block, BlockHash, MerkleBlock, OutPoint, Script, Transaction,
+    Txid,
 };
+use bitcoin::{Address, Network};
 
-#[allow(unused_imports)]
-use log::{debug, error, info, trace};
+use futures::future::{self, Either};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use log::{debug, trace};
 
-use reqwest::{header, Client, Response};
+use reqwest::{header, Client, Method, Response, StatusCode};
 
 use crate::api::AddressStats;
+use crate::Instant;
 use crate::{
-    BlockStatus, BlockSummary, Builder, Error, MerkleProof, OutputStatus, Tx, TxStatus,
-    BASE_BACKOFF_MILLIS, RETRYABLE_ERROR_CODES,
+    byte_range, check_header_chain, http_response_error, idempotency_key, jitter,
+    network_from_genesis_hash, next_last_seen, next_last_seen_of, parse_header, paths,
+    retry_after_delay, slice_range, sort_fee_estimates, trim_base_url, validate_address_prefix,
+    validate_base_url, BlockInfo, BlockStatus, BlockSummary, Builder, Error, FeeCache,
+    JsonArrayParser, MempoolRecentTx, MempoolStats, MerkleProof, OutputStatus, RequestFormat,
+    RequestObserver, ScriptHashStats, Tx, TxStatus, TxWithSpends, Utxo, UtxoFilter,
+    BASE_BACKOFF_MILLIS, BLOCK_SUMMARIES_PAGE_SIZE, DEFAULT_USER_AGENT, IDEMPOTENCY_KEY_HEADER,
+    RETRYABLE_ERROR_CODES,
 };
-
+#[cfg(feature = "elements")]
+use crate::{validate_asset_id, AssetInfo, AssetTx};
+#[cfg(feature = "mempool-space")]
+use crate::{DifficultyAdjustment, RecommendedFees};
+
+/// Default number of concurrent requests made by batched methods such as
+/// [`AsyncClient::get_txs`].
+pub const DEFAULT_CONCURRENCY: usize = 10;
+
+/// Async Esplora client, sleeping between retries with the [`Sleeper`] `S`.
+///
+/// # Cancellation
+///
+/// All of its methods are cancellation-safe: dropping the returned future,
+/// e.g. on a timeout, aborts the request in flight or the delay before the
+/// next retry or poll, without any work carrying on in the background. This
+/// includes methods that may run indefinitely such as
+/// [`wait_for_tx_status`](AsyncClient::wait_for_tx_status), which callers
+/// stop this way.
 #[derive(Debug, Clone)]
 pub struct AsyncClient<S = DefaultSleeper> {
     /// The URL of the Esplora Server.
     url: String,
     /// The inner [`reqwest::Client`] to make HTTP requests.
     client: Client,
+    /// HTTP headers to set on every request, on top of the [`Client`]'s own
+    /// default headers.
+    headers: header::HeaderMap,
     /// Number of times to retry a request
     max_retries: usize,
+    /// Whether to randomize the backoff delay between retries
+    retry_jitter: bool,
+    /// Response status codes for which a request is retried
+    retryable_codes: Vec<u16>,
+    /// The fee estimates cache, if enabled.
+    fee_cache: Option<FeeCache>,
+    /// Callback invoked after each request attempt, if any.
+    on_request: Option<RequestObserver>,
+    /// Bound on the whole sequence of attempts of a request, if any.
+    total_timeout: Option<Duration>,
+    /// Whether to send an idempotency key with each broadcast.
+    idempotency: bool,
+    /// Function to sleep with instead of the [`Sleeper`], only set on clients
+    /// built with [`Builder::build_async_with_boxed_sleeper`].
+    sleep_fn: Option<SleepFn>,
 
     /// Marker for the type of sleeper used
     marker: PhantomData<S>,
@@ -49,7 +98,86 @@ pub struct AsyncClient<S = DefaultSleeper> {
 
 impl<S: Sleeper> AsyncClient<S> {
     /// Build an async client from a builder
+    ///
+    /// Returns [`Error::InvalidBaseUrl`] if the base URL isn't an absolute
+    /// `http` or `https` URL.
     pub fn from_builder(builder: Builder) -> Result<Self, Error> {
+        AsyncClient::from_builder_inner(builder, None)
+    }
+
+    /// Build an async client from a builder and a pre-built [`Client`].
+    ///
+    /// The proxy, timeouts and pool options of the builder are ignored, they
+    /// have to be set on the given [`Client`] instead. The headers of the
+    /// builder, as well as its `User-Agent` if set, are sent with every request
+    /// on top of the [`Client`]'s default headers, taking precedence over them
+    /// when both set the same header.
+    pub fn from_builder_with_client(builder: Builder, client: Client) -> Result<Self, Error> {
+        validate_base_url(&builder.base_url)?;
+        let mut headers = header_map(builder.headers)?;
+        if let Some(user_agent) = builder.user_agent {
+            if !headers.contains_key(header::USER_AGENT) {
+                let user_agent = header::HeaderValue::from_str(&user_agent)
+                    .map_err(|_| Error::InvalidHttpHeaderValue(user_agent))?;
+                headers.insert(header::USER_AGENT, user_agent);
+            }
+        }
+
+        Ok(AsyncClient {
+            url: trim_base_url(builder.base_url),
+            client,
+            headers,
+            max_retries: builder.max_retries,
+            retry_jitter: builder.retry_jitter,
+            retryable_codes: builder.retryable_codes,
+            fee_cache: builder
+                .fee_cache_ttl
+                .map(|ttl| FeeCache::new(ttl, builder.on_cache.clone())),
+            on_request: builder.on_request,
+            total_timeout: builder.total_timeout,
+            idempotency: builder.idempotency,
+            sleep_fn: None,
+            marker: PhantomData,
+        })
+    }
+
+    pub fn from_client(url: String, client: Client) -> Self {
+        AsyncClient {
+            url: trim_base_url(url),
+            client,
+            headers: header::HeaderMap::new(),
+            max_retries: crate::DEFAULT_MAX_RETRIES,
+            retry_jitter: false,
+            retryable_codes: RETRYABLE_ERROR_CODES.to_vec(),
+            fee_cache: None,
+            on_request: None,
+            total_timeout: None,
+            idempotency: false,
+            sleep_fn: None,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl AsyncClient<BoxedSleeper> {
+    /// Build an async client from a builder, sleeping between retries and
+    /// while polling with the given `sleep_fn` rather than with a [`Sleeper`]
+    /// fixed at compile time.
+    ///
+    /// This allows picking the async runtime to sleep with at runtime.
+    pub fn from_builder_with_sleep_fn(
+        builder: Builder,
+        sleep_fn: impl Fn(Duration) -> BoxedSleep + Send + Sync + 'static,
+    ) -> Result<Self, Error> {
+        AsyncClient::from_builder_inner(builder, Some(SleepFn(Arc::new(sleep_fn))))
+    }
+}
+
+impl<S: Sleeper> AsyncClient<S> {
+    /// Build an async client from a builder, sleeping with the given
+    /// `sleep_fn` if any, or with the [`Sleeper`] otherwise.
+    fn from_builder_inner(builder: Builder, sleep_fn: Option<SleepFn>) -> Result<Self, Error> {
+        validate_base_url(&builder.base_url)?;
         let mut client_builder = Client::builder();
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -58,37 +186,96 @@ impl<S: Sleeper> AsyncClient<S> {
         }
 
         #[cfg(not(target_arch = "wasm32"))]
-        if let Some(timeout) = builder.timeout {
-            client_builder = client_builder.timeout(core::time::Duration::from_secs(timeout));
+        if let Some(timeout) = builder
+            .timeout_duration
+            .or_else(|| builder.timeout.map(core::time::Duration::from_secs))
+        {
+            client_builder = client_builder.timeout(timeout);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(connect_timeout) = builder.connect_timeout {
+            client_builder = client_builder.connect_timeout(connect_timeout);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(policy) = builder.redirect_policy {
+            client_builder = client_builder.redirect(match policy {
+                crate::RedirectPolicy::None => reqwest::redirect::Policy::none(),
+                // `reqwest` counts the original request along with the redirects.
+                crate::RedirectPolicy::Limited(max) => {
+                    reqwest::redirect::Policy::limited(max.saturating_add(1))
+                }
+            });
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(max) = builder.pool_max_idle_per_host {
+            client_builder = client_builder.pool_max_idle_per_host(max);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(timeout) = builder.pool_idle_timeout {
+            client_builder = client_builder.pool_idle_timeout(timeout);
+        }
+
+        let user_agent = builder.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+        client_builder = client_builder.user_agent(user_agent);
+
         if !builder.headers.is_empty() {
-            let mut headers = header::HeaderMap::new();
-            for (k, v) in builder.headers {
-                let header_name = header::HeaderName::from_lowercase(k.to_lowercase().as_bytes())
-                    .map_err(|_| Error::InvalidHttpHeaderName(k))?;
-                let header_value = header::HeaderValue::from_str(&v)
-                    .map_err(|_| Error::InvalidHttpHeaderValue(v))?;
-                headers.insert(header_name, header_value);
-            }
-            client_builder = client_builder.default_headers(headers);
+            client_builder = client_builder.default_headers(header_map(builder.headers)?);
         }
 
         Ok(AsyncClient {
-            url: builder.base_url,
+            url: trim_base_url(builder.base_url),
             client: client_builder.build()?,
+            headers: header::HeaderMap::new(),
             max_retries: builder.max_retries,
+            retry_jitter: builder.retry_jitter,
+            retryable_codes: builder.retryable_codes,
+            fee_cache: builder
+                .fee_cache_ttl
+                .map(|ttl| FeeCache::new(ttl, builder.on_cache.clone())),
+            on_request: builder.on_request,
+            total_timeout: builder.total_timeout,
+            idempotency: builder.idempotency,
+            sleep_fn,
             marker: PhantomData,
         })
     }
 
-    pub fn from_client(url: String, client: Client) -> Self {
-        AsyncClient {
-            url,
-            client,
-            max_retries: crate::DEFAULT_MAX_RETRIES,
-            marker: PhantomData,
+    /// Sleep for `dur` with the client's sleep function if it was built with
+    /// one, or with its [`Sleeper`] otherwise.
+    fn sleep(&self, dur: Duration) -> Either<S::Sleep, BoxedSleep> {
+        match &self.sleep_fn {
+            Some(sleep_fn) => Either::Right((sleep_fn.0)(dur)),
+            None => Either::Left(S::sleep(dur)),
+        }
+    }
+
+    /// Make an HTTP GET request to the given `path` for a body in the given
+    /// `format`, returning the response if its status is a success.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error either from the HTTP client, or
+    /// [`Error::HttpResponse`] if the status isn't a success.
+    async fn get_response_with_format(
+        &self,
+        path: &str,
+        format: RequestFormat,
+    ) -> Result<Response, Error> {
+        let url = format!("{}{}", self.url, path);
+        let response = self
+            .get_with_retry_and_headers(&url, format.headers())
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            return Err(http_response_error(status, response.text().await?));
         }
+
+        Ok(response)
     }
 
     /// Make an HTTP GET request to given URL, deserializing to any `T` that
@@ -103,15 +290,9 @@ impl<S: Sleeper> AsyncClient<S> {
     /// This function will return an error either from the HTTP client, or the
     /// [`bitcoin::consensus::Decodable`] deserialization.
     async fn get_response<T: Decodable>(&self, path: &str) -> Result<T, Error> {
-        let url = format!("{}{}", self.url, path);
-        let response = self.get_with_retry(&url).await?;
-
-        if !response.status().is_success() {
-            return Err(Error::HttpResponse {
-                status: response.status().as_u16(),
-                message: response.text().await?,
-            });
-        }
+        let response = self
+            .get_response_with_format(path, RequestFormat::Path)
+            .await?;
 
         Ok(deserialize::<T>(&response.bytes().await?)?)
     }
@@ -143,17 +324,11 @@ impl<S: Sleeper> AsyncClient<S> {
         &self,
         path: &str,
     ) -> Result<T, Error> {
-        let url = format!("{}{}", self.url, path);
-        let response = self.get_with_retry(&url).await?;
+        let response = self
+            .get_response_with_format(path, RequestFormat::Path)
+            .await?;
 
-        if !response.status().is_success() {
-            return Err(Error::HttpResponse {
-                status: response.status().as_u16(),
-                message: response.text().await?,
-            });
-        }
-
-        response.json::<T>().await.map_err(Error::Reqwest)
+        response.json::<T>().await.map_err(Error::from)
     }
 
     /// Make an HTTP GET request to given URL, deserializing to `Option<T>`.
@@ -185,15 +360,9 @@ impl<S: Sleeper> AsyncClient<S> {
     /// This function will return an error either from the HTTP client, or the
     /// [`bitcoin::consensus::Decodable`] deserialization.
     async fn get_response_hex<T: Decodable>(&self, path: &str) -> Result<T, Error> {
-        let url = format!("{}{}", self.url, path);
-        let response = self.get_with_retry(&url).await?;
-
-        if !response.status().is_success() {
-            return Err(Error::HttpResponse {
-                status: response.status().as_u16(),
-                message: response.text().await?,
-            });
-        }
+        let response = self
+            .get_response_with_format(path, RequestFormat::Path)
+            .await?;
 
         let hex_str = response.text().await?;
         Ok(deserialize(&Vec::from_hex(&hex_str)?)?)
@@ -222,15 +391,9 @@ impl<S: Sleeper> AsyncClient<S> {
     ///
     /// This function will return an error either from the HTTP client.
     async fn get_response_text(&self, path: &str) -> Result<String, Error> {
-        let url = format!("{}{}", self.url, path);
-        let response = self.get_with_retry(&url).await?;
-
-        if !response.status().is_success() {
-            return Err(Error::HttpResponse {
-                status: response.status().as_u16(),
-                message: response.text().await?,
-            });
-        }
+        let response = self
+            .get_response_with_format(path, RequestFormat::Path)
+            .await?;
 
         Ok(response.text().await?)
     }
@@ -249,6 +412,32 @@ impl<S: Sleeper> AsyncClient<S> {
         }
     }
 
+    /// Make an HTTP GET request to given URL, returning the body of the
+    /// response as is.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error either from the HTTP client.
+    async fn get_response_bytes(&self, path: &str) -> Result<Vec<u8>, Error> {
+        let response = self
+            .get_response_with_format(path, RequestFormat::Path)
+            .await?;
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Make an HTTP GET request to given URL, returning the body of the
+    /// response as is, or `None` if not found.
+    ///
+    /// It uses [`AsyncEsploraClient::get_response_bytes`] internally.
+    async fn get_opt_response_bytes(&self, path: &str) -> Result<Option<Vec<u8>>, Error> {
+        match self.get_response_bytes(path).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(Error::HttpResponse { status: 404, .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Make an HTTP POST request to given URL, serializing from any `T` that
     /// implement [`bitcoin::consensus::Encodable`].
     ///
@@ -259,17 +448,39 @@ impl<S: Sleeper> AsyncClient<S> {
     ///
     /// This function will return an error either from the HTTP client, or the
     /// [`bitcoin::consensus::Encodable`] serialization.
-    async fn post_request_hex<T: Encodable>(&self, path: &str, body: T) -> Result<(), Error> {
-        let url = format!("{}{}", self.url, path);
+    async fn post_request_hex<T: Encodable>(
+        &self,
+        path: &str,
+        body: T,
+        headers: &[(&str, &str)],
+    ) -> Result<(), Error> {
         let body = serialize::<T>(&body).to_lower_hex_string();
+        self.post_request_text(path, body, headers).await
+    }
+
+    /// Make an HTTP POST request to the given `path` with the given `body`,
+    /// sent as is, setting `headers` on top of the client's own, along with an
+    /// idempotency key if enabled.
+    async fn post_request_text(
+        &self,
+        path: &str,
+        body: String,
+        headers: &[(&str, &str)],
+    ) -> Result<(), Error> {
+        let url = format!("{}{}", self.url, path);
+        let mut headers = extra_header_map(headers)?;
+        if self.idempotency && !headers.contains_key(IDEMPOTENCY_KEY_HEADER) {
+            let key = header::HeaderValue::from_str(&idempotency_key())
+                .expect("a UUID is a valid header value");
+            headers.insert(IDEMPOTENCY_KEY_HEADER, key);
+        }
 
-        let response = self.client.post(url).body(body).send().await?;
+        debug!("POST {}", url);
+        let response = self.post_with_retry(&url, &body, &headers).await?;
 
         if !response.status().is_success() {
-            return Err(Error::HttpResponse {
-                status: response.status().as_u16(),
-                message: response.text().await?,
-            });
+            let status = response.status().as_u16();
+            return Err(http_response_error(status, response.text().await?));
         }
 
         Ok(())
@@ -277,7 +488,23 @@ impl<S: Sleeper> AsyncClient<S> {
 
     /// Get a [`Transaction`] option given its [`Txid`]
     pub async fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, Error> {
-        self.get_opt_response(&format!("/tx/{txid}/raw")).await
+        self.get_opt_response(&paths::tx_raw(txid)).await
+    }
+
+    /// Get the hex-encoded raw [`Transaction`] option given its [`Txid`],
+    /// exactly as returned by the server.
+    pub async fn get_tx_hex(&self, txid: &Txid) -> Result<Option<String>, Error> {
+        self.get_opt_response_text(&paths::tx_hex(txid)).await
+    }
+
+    /// Get the raw [`Transaction`] option given its [`Txid`], as the exact
+    /// bytes returned by the server.
+    ///
+    /// Unlike [`get_tx`](Self::get_tx), the bytes aren't deserialized, so
+    /// they can be relayed or hashed without any risk of being serialized
+    /// differently.
+    pub async fn get_tx_raw_bytes(&self, txid: &Txid) -> Result<Option<Vec<u8>>, Error> {
+        self.get_opt_response_bytes(&paths::tx_raw(txid)).await
     }
 
     /// Get a [`Transaction`] given its [`Txid`].
@@ -289,6 +516,32 @@ impl<S: Sleeper> AsyncClient<S> {
         }
     }
 
+    /// Get many [`Transaction`]s given their [`Txid`]s, returned in the same
+    /// order as `txids`, with `None` for the ones that were not found.
+    ///
+    /// Up to [`DEFAULT_CONCURRENCY`] requests are made concurrently, see
+    /// [`AsyncClient::get_txs_with_concurrency`] to change this limit.
+    pub async fn get_txs(&self, txids: &[Txid]) -> Result<Vec<Option<Transaction>>, Error> {
+        self.get_txs_with_concurrency(txids, DEFAULT_CONCURRENCY)
+            .await
+    }
+
+    /// Get many [`Transaction`]s given their [`Txid`]s, making up to
+    /// `concurrency` requests at once.
+    ///
+    /// See [`AsyncClient::get_txs`].
+    pub async fn get_txs_with_concurrency(
+        &self,
+        txids: &[Txid],
+        concurrency: usize,
+    ) -> Result<Vec<Option<Transaction>>, Error> {
+        stream::iter(txids)
+            .map(|txid| self.get_tx(txid))
+            .buffered(concurrency.max(1))
+            .try_collect()
+            .await
+    }
+
     /// Get a [`Txid`] of a transaction given its index in a block with a given
     /// hash.
     pub async fn get_txid_at_block_index(
@@ -297,7 +550,7 @@ impl<S: Sleeper> AsyncClient<S> {
         index: usize,
     ) -> Result<Option<Txid>, Error> {
         match self
-            .get_opt_response_text(&format!("/block/{block_hash}/txid/{index}"))
+            .get_opt_response_text(&paths::block_txid(block_hash, index))
             .await?
         {
             Some(s) => Ok(Some(Txid::from_str(&s).map_err(Error::HexToArray)?)),
@@ -305,45 +558,227 @@ impl<S: Sleeper> AsyncClient<S> {
         }
     }
 
+    /// Get the coinbase [`Transaction`] of the block with the given hash, or
+    /// `None` if the block is unknown.
+    ///
+    /// The coinbase is always the first transaction of a block, so this looks
+    /// up its [`Txid`] at index `0` with
+    /// [`get_txid_at_block_index`](Self::get_txid_at_block_index), then fetches
+    /// it with [`get_tx`](Self::get_tx).
+    pub async fn get_block_coinbase(
+        &self,
+        block_hash: &BlockHash,
+    ) -> Result<Option<Transaction>, Error> {
+        match self.get_txid_at_block_index(block_hash, 0).await? {
+            Some(txid) => self.get_tx(&txid).await,
+            None => Ok(None),
+        }
+    }
+
     /// Get the status of a [`Transaction`] given its [`Txid`].
     pub async fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, Error> {
-        self.get_response_json(&format!("/tx/{txid}/status")).await
+        self.get_response_json(&paths::tx_status(txid)).await
     }
 
     /// Get transaction info given it's [`Txid`].
     pub async fn get_tx_info(&self, txid: &Txid) -> Result<Option<Tx>, Error> {
-        self.get_opt_response_json(&format!("/tx/{txid}")).await
+        self.get_opt_response_json(&paths::tx(txid)).await
+    }
+
+    /// Get the number of confirmations of a [`Transaction`] given its
+    /// [`Txid`], `0` if it is in the mempool and `None` if it is unknown.
+    ///
+    /// The transaction and the current tip are fetched concurrently.
+    pub async fn get_tx_confirmations(&self, txid: &Txid) -> Result<Option<u32>, Error> {
+        let (tx, tip_height) = futures::try_join!(self.get_tx_info(txid), self.get_height())?;
+        Ok(tx.map(|tx| tx.status.confirmations(tip_height)))
+    }
+
+    /// Wait until the transaction with the given [`Txid`] has at least
+    /// `target_confs` confirmations, returning its latest [`TxStatus`].
+    ///
+    /// The transaction is polled every `poll_interval`, sleeping with the
+    /// client's [`Sleeper`]. A transaction in the mempool has zero
+    /// confirmations, so a `target_confs` of `0` only waits for it to be
+    /// known by the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TransactionNotFound`] if the server doesn't know the
+    /// transaction, e.g. because it was evicted from the mempool or replaced.
+    ///
+    /// This loops indefinitely while the transaction stays unconfirmed, so
+    /// callers should wrap it in a timeout, e.g. `tokio::time::timeout`.
+    pub async fn wait_for_tx_status(
+        &self,
+        txid: &Txid,
+        target_confs: u32,
+        poll_interval: core::time::Duration,
+    ) -> Result<TxStatus, Error> {
+        loop {
+            let status = match self.get_tx_info(txid).await? {
+                Some(tx) => tx.status,
+                None => return Err(Error::TransactionNotFound(*txid)),
+            };
+            let confirmations = if status.confirmed {
+                status.confirmations(self.get_height().await?)
+            } else {
+                0
+            };
+            if confirmations >= target_confs {
+                return Ok(status);
+            }
+            self.sleep(poll_interval).await;
+        }
     }
 
     /// Get a [`BlockHeader`] given a particular block hash.
     pub async fn get_header_by_hash(&self, block_hash: &BlockHash) -> Result<BlockHeader, Error> {
-        self.get_response_hex(&format!("/block/{block_hash}/header"))
+        self.get_response_hex(&paths::block_header(block_hash))
             .await
     }
 
+    /// Get a [`BlockHeader`] given a particular block height.
+    ///
+    /// This resolves the [`BlockHash`] at `height` first, so it takes two
+    /// requests. Returns [`Error::HeaderHeightNotFound`] if `height` is above
+    /// the current tip.
+    pub async fn get_header_by_height(&self, height: u32) -> Result<BlockHeader, Error> {
+        let block_hash = self.get_block_hash_at(height).await?;
+        self.get_header_by_hash(&block_hash).await
+    }
+
+    /// Get the [`BlockHeader`]s of the `count` blocks starting at
+    /// `start_height`, along with their heights.
+    ///
+    /// The block hashes are resolved first, then the headers are fetched, up
+    /// to [`DEFAULT_CONCURRENCY`] at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidResponse`] if a header doesn't build on the
+    /// previous one, e.g. because the chain was reorganized while fetching
+    /// them, and [`Error::HeaderHeightNotFound`] if a height is above the
+    /// current tip.
+    pub async fn get_headers(
+        &self,
+        start_height: u32,
+        count: u32,
+    ) -> Result<Vec<(u32, BlockHeader)>, Error> {
+        let heights = start_height..start_height.saturating_add(count);
+        let block_hashes: Vec<BlockHash> = stream::iter(heights.clone())
+            .map(|height| self.get_block_hash_at(height))
+            .buffered(DEFAULT_CONCURRENCY)
+            .try_collect()
+            .await?;
+        let headers: Vec<BlockHeader> = stream::iter(&block_hashes)
+            .map(|block_hash| self.get_header_by_hash(block_hash))
+            .buffered(DEFAULT_CONCURRENCY)
+            .try_collect()
+            .await?;
+        let headers: Vec<(u32, BlockHeader)> = heights.zip(headers).collect();
+        check_header_chain(&headers)?;
+        Ok(headers)
+    }
+
+    /// Get the [`BlockHash`] at `height`, mapping a `404` response to
+    /// [`Error::HeaderHeightNotFound`].
+    async fn get_block_hash_at(&self, height: u32) -> Result<BlockHash, Error> {
+        match self.get_block_hash(height).await {
+            Err(Error::HttpResponse { status: 404, .. }) => {
+                Err(Error::HeaderHeightNotFound(height))
+            }
+            result => result,
+        }
+    }
+
     /// Get the [`BlockStatus`] given a particular [`BlockHash`].
     pub async fn get_block_status(&self, block_hash: &BlockHash) -> Result<BlockStatus, Error> {
-        self.get_response_json(&format!("/block/{block_hash}/status"))
+        self.get_response_json(&paths::block_status(block_hash))
             .await
     }
 
+    /// Get the [`BlockInfo`] of a block given its [`BlockHash`], `None` if
+    /// the block isn't known.
+    pub async fn get_block_info(&self, block_hash: &BlockHash) -> Result<Option<BlockInfo>, Error> {
+        self.get_opt_response_json(&paths::block(block_hash)).await
+    }
+
     /// Get a [`Block`] given a particular [`BlockHash`].
     pub async fn get_block_by_hash(&self, block_hash: &BlockHash) -> Result<Option<Block>, Error> {
-        self.get_opt_response(&format!("/block/{block_hash}/raw"))
+        self.get_opt_response(&paths::block_raw(block_hash)).await
+    }
+
+    /// Get `len` bytes of the raw [`Block`] given its [`BlockHash`], starting
+    /// at byte `start` of its serialization.
+    ///
+    /// This allows downloading a large block in slices rather than at once,
+    /// see [`get_block_by_hash`](Self::get_block_by_hash) to get it whole.
+    /// The range is requested with an HTTP `Range` header, which Esplora
+    /// itself ignores: only servers (or proxies in front of them) supporting
+    /// range requests save the download of the whole block, which is
+    /// otherwise sliced once received. Fewer than `len` bytes are returned if
+    /// the block ends before, none if it ends before `start`.
+    pub async fn get_block_raw_range(
+        &self,
+        block_hash: &BlockHash,
+        start: usize,
+        len: usize,
+    ) -> Result<Vec<u8>, Error> {
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        let url = format!("{}{}", self.url, paths::block_raw(block_hash));
+        let range = byte_range(start, len);
+        let response = self
+            .get_with_retry_and_headers(&url, &[("Range", &range)])
+            .await?;
+
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT => {
+                let mut bytes = response.bytes().await?.to_vec();
+                bytes.truncate(len);
+                Ok(bytes)
+            }
+            StatusCode::OK => Ok(slice_range(&response.bytes().await?, start, len)),
+            StatusCode::RANGE_NOT_SATISFIABLE => Ok(Vec::new()),
+            status => Err(http_response_error(status.as_u16(), response.text().await?)),
+        }
+    }
+
+    /// Get all the [`Txid`]s of the transactions in a [`Block`], in block
+    /// order, given its [`BlockHash`].
+    pub async fn get_block_txids(&self, block_hash: &BlockHash) -> Result<Vec<Txid>, Error> {
+        self.get_response_json(&paths::block_txids(block_hash))
             .await
     }
 
+    /// Get up to 25 [`Tx`]s from a [`Block`] given its [`BlockHash`],
+    /// beginning at `start_index` (starts from 0 if `start_index` is `None`).
+    ///
+    /// The `start_index` value MUST be a multiple of 25,
+    /// else an error will be returned by Esplora.
+    pub async fn get_block_txs(
+        &self,
+        block_hash: &BlockHash,
+        start_index: Option<usize>,
+    ) -> Result<Vec<Tx>, Error> {
+        let path = paths::block_txs(block_hash, start_index);
+
+        self.get_response_json(&path).await
+    }
+
     /// Get a merkle inclusion proof for a [`Transaction`] with the given
     /// [`Txid`].
     pub async fn get_merkle_proof(&self, tx_hash: &Txid) -> Result<Option<MerkleProof>, Error> {
-        self.get_opt_response_json(&format!("/tx/{tx_hash}/merkle-proof"))
+        self.get_opt_response_json(&paths::tx_merkle_proof(tx_hash))
             .await
     }
 
     /// Get a [`MerkleBlock`] inclusion proof for a [`Transaction`] with the
     /// given [`Txid`].
     pub async fn get_merkle_block(&self, tx_hash: &Txid) -> Result<Option<MerkleBlock>, Error> {
-        self.get_opt_response_hex(&format!("/tx/{tx_hash}/merkleblock-proof"))
+        self.get_opt_response_hex(&paths::tx_merkleblock_proof(tx_hash))
             .await
     }
 
@@ -354,40 +789,276 @@ impl<S: Sleeper> AsyncClient<S> {
         txid: &Txid,
         index: u64,
     ) -> Result<Option<OutputStatus>, Error> {
-        self.get_opt_response_json(&format!("/tx/{txid}/outspend/{index}"))
+        self.get_opt_response_json(&paths::tx_outspend(txid, index))
             .await
     }
 
-    /// Broadcast a [`Transaction`] to Esplora
+    /// Get the [`Transaction`] spending an output given a [`Txid`] and the
+    /// output index, or `None` if the output is unspent.
+    ///
+    /// This takes two requests: the spending status of the output with
+    /// [`get_output_status`](Self::get_output_status), then the spending
+    /// transaction with [`get_tx`](Self::get_tx) if the output is spent.
+    pub async fn get_output_spender(
+        &self,
+        txid: &Txid,
+        index: u64,
+    ) -> Result<Option<Transaction>, Error> {
+        match self.get_output_status(txid, index).await? {
+            Some(OutputStatus {
+                spent: true,
+                txid: Some(spender),
+                ..
+            }) => self.get_tx(&spender).await,
+            _ => Ok(None),
+        }
+    }
+
+    /// Get the spending status of the output at `outpoint`.
+    ///
+    /// See [`get_output_status`](Self::get_output_status).
+    pub async fn get_outpoint_status(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<Option<OutputStatus>, Error> {
+        self.get_output_status(&outpoint.txid, outpoint.vout.into())
+            .await
+    }
+
+    /// Get the spending status of the outputs at many `outpoints`, paired
+    /// with their [`OutPoint`], in the order of `outpoints`.
+    ///
+    /// Up to [`DEFAULT_CONCURRENCY`] requests are made at once. As with
+    /// [`get_output_status`](Self::get_output_status), the status is `None`
+    /// for an outpoint the server responds to with a `404`.
+    pub async fn get_outpoints_spent(
+        &self,
+        outpoints: &[OutPoint],
+    ) -> Result<Vec<(OutPoint, Option<OutputStatus>)>, Error> {
+        stream::iter(outpoints)
+            .map(|outpoint| async move {
+                let status = self.get_outpoint_status(outpoint).await?;
+                Ok::<_, Error>((*outpoint, status))
+            })
+            .buffered(DEFAULT_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    /// Get the spending status of all the outputs of a [`Transaction`] given
+    /// its [`Txid`], in output order.
+    pub async fn get_output_statuses(&self, txid: &Txid) -> Result<Vec<OutputStatus>, Error> {
+        self.get_response_json(&paths::tx_outspends(txid)).await
+    }
+
+    /// Get transaction info given its [`Txid`] along with the spending status
+    /// of each of its outputs, combining [`get_tx_info`](Self::get_tx_info)
+    /// and [`get_output_statuses`](Self::get_output_statuses).
+    ///
+    /// Both are requested concurrently.
+    pub async fn get_tx_with_spends(&self, txid: &Txid) -> Result<Option<TxWithSpends>, Error> {
+        let (tx, outspends) =
+            futures::join!(self.get_tx_info(txid), self.get_output_statuses(txid));
+        match tx? {
+            Some(tx) => Ok(Some(TxWithSpends {
+                tx,
+                outspends: outspends?,
+            })),
+            // Requesting the outspends of an unknown transaction fails instead.
+            None => Ok(None),
+        }
+    }
+
+    /// Broadcast a [`Transaction`] to Esplora.
+    ///
+    /// Esplora has no equivalent of Bitcoin Core's `testmempoolaccept`, so
+    /// there is no way to check that a transaction would be accepted without
+    /// relaying it. A transaction rejected by the server's node, e.g. because
+    /// it conflicts with one in the mempool, returns [`Error::HttpResponse`]
+    /// with the node's reject reason as the message.
+    ///
+    /// A broadcast failing with one of the
+    /// [retryable codes](crate::Builder::retryable_codes) is retried like any
+    /// other request, except for `400 Bad Request`, the status of a rejection.
     pub async fn broadcast(&self, transaction: &Transaction) -> Result<(), Error> {
-        self.post_request_hex("/tx", transaction).await
+        self.broadcast_with_headers(transaction, &[]).await
+    }
+
+    /// Broadcast a [`Transaction`] to Esplora, setting the given `headers` on
+    /// this request only, e.g. a request-scoped API key.
+    ///
+    /// The headers of the client are still sent, except those overridden by
+    /// `headers`, compared case-insensitively.
+    pub async fn broadcast_with_headers(
+        &self,
+        transaction: &Transaction,
+        headers: &[(&str, &str)],
+    ) -> Result<(), Error> {
+        self.post_request_hex(paths::BROADCAST, transaction, headers)
+            .await
+    }
+
+    /// Broadcast a transaction already serialized as hex.
+    ///
+    /// Unlike [`broadcast`](Self::broadcast), `tx_hex` is posted verbatim,
+    /// without being deserialized and serialized again. It is only checked to
+    /// be valid hex, otherwise [`Error::HexToBytes`] is returned.
+    pub async fn broadcast_raw(&self, tx_hex: &str) -> Result<(), Error> {
+        Vec::<u8>::from_hex(tx_hex)?;
+        self.post_request_text(paths::BROADCAST, tx_hex.to_string(), &[])
+            .await
     }
 
     /// Get the current height of the blockchain tip
     pub async fn get_height(&self) -> Result<u32, Error> {
-        self.get_response_text("/blocks/tip/height")
+        self.get_response_text(paths::TIP_HEIGHT)
             .await
             .map(|height| u32::from_str(&height).map_err(Error::Parsing))?
     }
 
     /// Get the [`BlockHash`] of the current blockchain tip.
     pub async fn get_tip_hash(&self) -> Result<BlockHash, Error> {
-        self.get_response_text("/blocks/tip/hash")
+        self.get_response_text(paths::TIP_HASH)
             .await
             .map(|block_hash| BlockHash::from_str(&block_hash).map_err(Error::HexToArray))?
     }
 
+    /// Get the height and [`BlockHash`] of the current blockchain tip.
+    ///
+    /// The two values are fetched concurrently with independent requests, so
+    /// in rare cases (e.g. a new block or a reorg in between) they may not
+    /// refer to the same block.
+    pub async fn get_tip(&self) -> Result<(u32, BlockHash), Error> {
+        futures::try_join!(self.get_height(), self.get_tip_hash())
+    }
+
+    /// Get the [`BlockSummary`] of the current blockchain tip.
+    ///
+    /// Some Esplora forks serve it in a single request from `/blocks/tip`,
+    /// which is tried first. Blockstream's esplora doesn't: it handles
+    /// `/blocks/tip` like `/blocks`, returning a list of the latest blocks,
+    /// while other backends may return an error. When the response isn't a
+    /// single summary, or is a `400` or `404` error, this falls back to
+    /// [`get_tip_hash`](Self::get_tip_hash) and
+    /// [`get_block_info`](Self::get_block_info), taking two more requests.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidResponse`] if the tip is no longer known by the
+    /// time its info is fetched, e.g. because of a reorg.
+    pub async fn get_tip_summary(&self) -> Result<BlockSummary, Error> {
+        match self.get_opt_response_text(paths::TIP).await {
+            Ok(Some(body)) => {
+                if let Ok(summary) = serde_json::from_str(&body) {
+                    return Ok(summary);
+                }
+            }
+            Ok(None) | Err(Error::HttpResponse { status: 400, .. }) => {}
+            Err(e) => return Err(e),
+        }
+        let tip_hash = self.get_tip_hash().await?;
+        match self.get_block_info(&tip_hash).await? {
+            Some(info) => Ok(info.into()),
+            None => Err(Error::InvalidResponse {
+                endpoint: paths::block(&tip_hash),
+                reason: "tip block not found, the chain may have been reorganized".to_string(),
+            }),
+        }
+    }
+
+    /// Wait until the blockchain tip differs from `current_tip`, then return
+    /// the height and [`BlockHash`] of the new tip.
+    ///
+    /// Esplora has no push notifications, so this is polling-based: the tip
+    /// hash is fetched every `poll_interval`, sleeping with the client's
+    /// [`Sleeper`] in between. As blocks are ten minutes apart on average, an
+    /// interval of 10 to 30 seconds is usually enough.
+    ///
+    /// The height is looked up from the status of the new tip, so unlike
+    /// [`get_tip`](Self::get_tip) both values refer to the same block. A tip
+    /// that is no longer in the best chain by then is skipped.
+    ///
+    /// This loops indefinitely while no new block is found, so callers should
+    /// wrap it in a timeout, e.g. `tokio::time::timeout`.
+    pub async fn wait_for_new_block(
+        &self,
+        current_tip: BlockHash,
+        poll_interval: core::time::Duration,
+    ) -> Result<(u32, BlockHash), Error> {
+        loop {
+            let tip = self.get_tip_hash().await?;
+            if tip != current_tip {
+                if let Some(height) = self.get_block_status(&tip).await?.height {
+                    return Ok((height, tip));
+                }
+            }
+            self.sleep(poll_interval).await;
+        }
+    }
+
     /// Get the [`BlockHash`] of a specific block height
     pub async fn get_block_hash(&self, block_height: u32) -> Result<BlockHash, Error> {
-        self.get_response_text(&format!("/block-height/{block_height}"))
+        self.get_response_text(&paths::block_height(block_height))
             .await
             .map(|block_hash| BlockHash::from_str(&block_hash).map_err(Error::HexToArray))?
     }
 
+    /// Get the [`Network`] of the server, identified by the hash of its
+    /// genesis block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownNetwork`] if the genesis block isn't the one of
+    /// a known network, such as a custom signet, see
+    /// [`get_network_with`](Self::get_network_with) for those.
+    pub async fn get_network(&self) -> Result<Network, Error> {
+        self.get_network_with(&[]).await
+    }
+
+    /// Get the [`Network`] of the server like
+    /// [`get_network`](Self::get_network), also matching the genesis block
+    /// hashes given in `extra`, e.g. the ones of custom signets.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownNetwork`] if the genesis block is neither the
+    /// one of a known network nor in `extra`.
+    pub async fn get_network_with(&self, extra: &[(BlockHash, Network)]) -> Result<Network, Error> {
+        let genesis_hash = self.get_block_hash(0).await?;
+        network_from_genesis_hash(genesis_hash, extra)
+    }
+
+    /// Get statistics about the mempool, including its fee rate histogram.
+    pub async fn get_mempool(&self) -> Result<MempoolStats, Error> {
+        self.get_response_json(paths::MEMPOOL).await
+    }
+
+    /// Get a list of the last 10 transactions to enter the mempool.
+    pub async fn get_mempool_recent(&self) -> Result<Vec<MempoolRecentTx>, Error> {
+        self.get_response_json(paths::MEMPOOL_RECENT).await
+    }
+
+    /// Get the [`Txid`]s of all transactions currently in the mempool.
+    ///
+    /// Note that this list can be very large on busy networks.
+    pub async fn get_mempool_txids(&self) -> Result<Vec<Txid>, Error> {
+        self.get_response_json(paths::MEMPOOL_TXIDS).await
+    }
+
     /// Get information about a specific address, includes confirmed balance and transactions in
     /// the mempool.
     pub async fn get_address_stats(&self, address: &Address) -> Result<AddressStats, Error> {
-        let path = format!("/address/{address}");
+        let path = paths::address(address);
+        self.get_response_json(&path).await
+    }
+
+    /// Get up to 10 addresses starting with the given `prefix`.
+    ///
+    /// The `prefix` must be a non-empty alphanumeric string no longer than an
+    /// address, otherwise [`Error::InvalidAddressPrefix`] is returned.
+    pub async fn get_address_prefix(&self, prefix: &str) -> Result<Vec<String>, Error> {
+        validate_address_prefix(prefix)?;
+        let path = paths::address_prefix(prefix);
         self.get_response_json(&path).await
     }
 
@@ -400,11 +1071,241 @@ impl<S: Sleeper> AsyncClient<S> {
         address: &Address,
         last_seen: Option<Txid>,
     ) -> Result<Vec<Tx>, Error> {
-        let path = match last_seen {
-            Some(last_seen) => format!("/address/{address}/txs/chain/{last_seen}"),
-            None => format!("/address/{address}/txs"),
-        };
+        let path = paths::address_txs(address, last_seen);
+
+        self.get_response_json(&path).await
+    }
+
+    /// Get a page of transaction history for the specified address, along
+    /// with the `last_seen` [`Txid`] to request the next page with, or `None`
+    /// if this is the last page.
+    ///
+    /// See [`AsyncClient::get_address_txs`].
+    pub async fn get_address_txs_page(
+        &self,
+        address: &Address,
+        last_seen: Option<Txid>,
+    ) -> Result<(Vec<Tx>, Option<Txid>), Error> {
+        let txs = self.get_address_txs(address, last_seen).await?;
+        let next = next_last_seen(&txs);
+        Ok((txs, next))
+    }
+
+    /// Get confirmed transaction history for the specified address, sorted
+    /// with newest first.
+    ///
+    /// Unlike [`AsyncClient::get_address_txs`], mempool transactions are never
+    /// included. Returns 25 transactions per page, more can be requested by
+    /// specifying the last txid seen by the previous query.
+    pub async fn get_address_txs_chain(
+        &self,
+        address: &Address,
+        last_seen: Option<Txid>,
+    ) -> Result<Vec<Tx>, Error> {
+        let path = paths::address_txs_chain(address, last_seen);
+        self.get_response_json(&path).await
+    }
+
+    /// Get the full confirmed transaction history for the specified address,
+    /// sorted with newest first.
+    ///
+    /// The history is paged through 25 transactions at a time with
+    /// [`AsyncClient::get_address_txs_chain`] until a short page is returned.
+    /// Since every page is requested from the last [`Txid`] of the previous
+    /// one, pages are fetched sequentially, so this can take many requests for
+    /// heavily used addresses. Transactions seen twice (e.g. because of a reorg
+    /// while paging) are only returned once.
+    pub async fn get_address_txs_chain_all(&self, address: &Address) -> Result<Vec<Tx>, Error> {
+        let mut txs = self.get_address_txs_chain(address, None).await?;
+        let mut seen: HashSet<Txid> = txs.iter().map(|tx| tx.txid).collect();
+        let mut next = next_last_seen(&txs);
+
+        while let Some(last_seen) = next {
+            let page = self.get_address_txs_chain(address, Some(last_seen)).await?;
+            next = next_last_seen(&page);
+
+            let txs_len = txs.len();
+            txs.extend(page.into_iter().filter(|tx| seen.insert(tx.txid)));
+            if txs.len() == txs_len {
+                break;
+            }
+        }
+
+        Ok(txs)
+    }
+
+    /// Get unspent transaction outputs for the specified address.
+    ///
+    /// Esplora doesn't page the UTXOs of an address, so the whole list is
+    /// loaded in memory at once, which can be large for heavily used addresses
+    /// such as exchange wallets. Backends may also refuse to list more UTXOs
+    /// than a configured limit, in which case [`Error::HttpResponse`] is
+    /// returned. See [`AsyncClient::address_utxos_stream`] to process them
+    /// as they are received instead.
+    pub async fn get_address_utxos(&self, address: &Address) -> Result<Vec<Utxo>, Error> {
+        let path = paths::address_utxos(address);
+        self.get_response_json(&path).await
+    }
+
+    /// Get the unspent transaction outputs for the specified address, keeping
+    /// only the confirmed or unconfirmed ones according to `filter`.
+    pub async fn get_address_utxos_filtered(
+        &self,
+        address: &Address,
+        filter: UtxoFilter,
+    ) -> Result<Vec<Utxo>, Error> {
+        let mut utxos = self.get_address_utxos(address).await?;
+        utxos.retain(|utxo| filter.matches(utxo));
+        Ok(utxos)
+    }
+
+    /// Stream the unspent transaction outputs for the specified address.
+    ///
+    /// Esplora doesn't page the UTXOs of an address, so they are still
+    /// requested at once, but unlike [`AsyncClient::get_address_utxos`] the
+    /// response is parsed incrementally: each [`Utxo`] is yielded as soon as it
+    /// has been received, without buffering the whole response body. The
+    /// stream ends after yielding an error.
+    pub fn address_utxos_stream<'a>(
+        &'a self,
+        address: &'a Address,
+    ) -> impl Stream<Item = Result<Utxo, Error>> + 'a {
+        let state = Some((None, JsonArrayParser::default()));
+        stream::try_unfold(state, move |state| async move {
+            let (response, mut parser) = match state {
+                Some(state) => state,
+                None => return Ok::<_, Error>(None),
+            };
+            let path = paths::address_utxos(address);
+            let invalid_response = |e: serde_json::Error| Error::InvalidResponse {
+                endpoint: path.clone(),
+                reason: e.to_string(),
+            };
+            let mut response = match response {
+                Some(response) => response,
+                None => {
+                    self.get_response_with_format(&path, RequestFormat::Path)
+                        .await?
+                }
+            };
+
+            while let Some(chunk) = response.chunk().await? {
+                let utxos: Vec<Utxo> = parser.push(&chunk).map_err(invalid_response)?;
+                if !utxos.is_empty() {
+                    return Ok(Some((utxos, Some((Some(response), parser)))));
+                }
+            }
+            parser.finish().map_err(invalid_response)?;
+            Ok(None)
+        })
+        .map_ok(|utxos| stream::iter(utxos.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Get the full transaction history for the specified address, sorted with
+    /// newest first, including up to 50 mempool transactions.
+    ///
+    /// The confirmed history is paged through 25 transactions at a time until a
+    /// short page is returned. Every page is requested from the last [`Txid`]
+    /// of the previous one, so pages can't be requested concurrently, but the
+    /// next page is prefetched while the current one is decoded: only the
+    /// txids and statuses of a page are parsed before requesting the next one.
+    /// Transactions seen twice (e.g. because of a reorg while paging) are only
+    /// returned once.
+    pub async fn get_address_txs_all(&self, address: &Address) -> Result<Vec<Tx>, Error> {
+        let mut txs = Vec::new();
+        let mut seen = HashSet::new();
+        let mut page = Some(self.get_address_txs_body(address, None).await?);
+
+        while let Some(body) = page.take() {
+            let next = next_last_seen_of(
+                parse_address_txs::<Vec<PagedTx>>(address, &body)?
+                    .into_iter()
+                    .map(|tx| (tx.txid, tx.status.confirmed)),
+            );
+            let prefetch = async {
+                match next {
+                    Some(last_seen) => self
+                        .get_address_txs_body(address, Some(last_seen))
+                        .await
+                        .map(Some),
+                    None => Ok(None),
+                }
+            };
+            // The request for the next page is sent first, so that it is in
+            // flight while the current page is decoded.
+            let decode = async { parse_address_txs::<Vec<Tx>>(address, &body) };
+            let (next_page, decoded) = future::join(prefetch, decode).await;
+
+            let txs_len = txs.len();
+            txs.extend(decoded?.into_iter().filter(|tx| seen.insert(tx.txid)));
+            // Stop on a page with nothing new rather than loop forever.
+            if txs_len > 0 && txs.len() == txs_len {
+                break;
+            }
+            page = next_page?;
+        }
+
+        Ok(txs)
+    }
+
+    /// Get the body of a page of transaction history for the specified
+    /// address, see [`AsyncClient::get_address_txs`].
+    async fn get_address_txs_body(
+        &self,
+        address: &Address,
+        last_seen: Option<Txid>,
+    ) -> Result<Vec<u8>, Error> {
+        let path = paths::address_txs(address, last_seen);
+        self.get_response_bytes(&path).await
+    }
+
+    /// Stream the full transaction history for the specified address, sorted
+    /// with newest first, including up to 50 mempool transactions.
+    ///
+    /// Unlike [`AsyncClient::get_address_txs_all`], the history is paged
+    /// through lazily: the next page of 25 confirmed transactions is only
+    /// requested once the transactions of the current one have all been
+    /// polled, so the history can be processed without holding it all in
+    /// memory. Transactions seen twice (e.g. because of a reorg while paging)
+    /// are only yielded once. The stream ends after yielding an error.
+    pub fn address_txs_stream<'a>(
+        &'a self,
+        address: &'a Address,
+    ) -> impl Stream<Item = Result<Tx, Error>> + 'a {
+        let state = (Some(None), HashSet::new());
+        stream::try_unfold(state, move |(next_page, mut seen)| async move {
+            let last_seen = match next_page {
+                Some(last_seen) => last_seen,
+                None => return Ok::<_, Error>(None),
+            };
+            let (txs, next) = self.get_address_txs_page(address, last_seen).await?;
+            let txs: Vec<Tx> = txs.into_iter().filter(|tx| seen.insert(tx.txid)).collect();
+            // Stop on a page with nothing new rather than loop forever.
+            let next_page = if txs.is_empty() { None } else { next.map(Some) };
+            Ok(Some((txs, (next_page, seen))))
+        })
+        .map_ok(|txs| stream::iter(txs.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Get unconfirmed transaction history for the specified address, sorted
+    /// with newest first. Returns up to 50 transactions (no paging).
+    pub async fn get_address_txs_mempool(&self, address: &Address) -> Result<Vec<Tx>, Error> {
+        let path = paths::address_txs_mempool(address);
+        self.get_response_json(&path).await
+    }
+
+    /// Get statistics about a particular [`Script`] hash's confirmed and mempool
+    /// transactions.
+    pub async fn get_scripthash_stats(&self, script: &Script) -> Result<ScriptHashStats, Error> {
+        let path = paths::scripthash(script);
+        self.get_response_json(&path).await
+    }
 
+    /// Get unspent transaction outputs for the specified [`Script`] hash.
+    pub async fn get_scripthash_utxos(&self, script: &Script) -> Result<Vec<Utxo>, Error> {
+        let path = paths::scripthash_utxos(script);
         self.get_response_json(&path).await
     }
 
@@ -417,19 +1318,138 @@ impl<S: Sleeper> AsyncClient<S> {
         script: &Script,
         last_seen: Option<Txid>,
     ) -> Result<Vec<Tx>, Error> {
-        let script_hash = sha256::Hash::hash(script.as_bytes());
-        let path = match last_seen {
-            Some(last_seen) => format!("/scripthash/{:x}/txs/chain/{}", script_hash, last_seen),
-            None => format!("/scripthash/{:x}/txs", script_hash),
-        };
+        let path = paths::scripthash_txs(script, last_seen);
+
+        self.get_response_json(&path).await
+    }
+
+    /// Get confirmed transaction history for the given script hash, the
+    /// SHA256 hash of a script's bytes under which Esplora indexes it.
+    ///
+    /// Same as [`scripthash_txs`](Self::scripthash_txs), for callers that only
+    /// have the hash of the script rather than the script itself.
+    pub async fn scripthash_txs_by_hash(
+        &self,
+        script_hash: &sha256::Hash,
+        last_seen: Option<Txid>,
+    ) -> Result<Vec<Tx>, Error> {
+        let path = paths::scripthash_txs_by_hash(script_hash, last_seen);
+        self.get_response_json(&path).await
+    }
 
+    /// Get a page of transaction history for the specified [`Script`] hash,
+    /// along with the `last_seen` [`Txid`] to request the next page with, or
+    /// `None` if this is the last page.
+    ///
+    /// See [`AsyncClient::scripthash_txs`].
+    pub async fn scripthash_txs_page(
+        &self,
+        script: &Script,
+        last_seen: Option<Txid>,
+    ) -> Result<(Vec<Tx>, Option<Txid>), Error> {
+        let txs = self.scripthash_txs(script, last_seen).await?;
+        let next = next_last_seen(&txs);
+        Ok((txs, next))
+    }
+
+    /// Get unconfirmed transaction history for the specified [`Script`] hash,
+    /// sorted with newest first. Returns up to 50 transactions (no paging).
+    pub async fn scripthash_txs_mempool(&self, script: &Script) -> Result<Vec<Tx>, Error> {
+        let path = paths::scripthash_txs_mempool(script);
         self.get_response_json(&path).await
     }
 
     /// Get an map where the key is the confirmation target (in number of
     /// blocks) and the value is the estimated feerate (in sat/vB).
+    ///
+    /// If the client was built with a [fee cache TTL](Builder::fee_cache_ttl),
+    /// the estimates may be served from the cache.
     pub async fn get_fee_estimates(&self) -> Result<HashMap<u16, f64>, Error> {
-        self.get_response_json("/fee-estimates").await
+        let fee_cache = match &self.fee_cache {
+            Some(fee_cache) => fee_cache,
+            None => return self.get_response_json(paths::FEE_ESTIMATES).await,
+        };
+        if let Some(estimates) = fee_cache.fresh() {
+            return Ok(estimates);
+        }
+        match self.get_response_json(paths::FEE_ESTIMATES).await {
+            Ok(estimates) => {
+                fee_cache.store(&estimates);
+                Ok(estimates)
+            }
+            Err(e) => fee_cache.stale().ok_or(e),
+        }
+    }
+
+    /// Get the fee estimates as `(target, feerate)` pairs sorted by ascending
+    /// confirmation target (in number of blocks), the feerate being in sat/vB.
+    ///
+    /// See [`get_fee_estimates`](Self::get_fee_estimates).
+    pub async fn get_fee_estimates_sorted(&self) -> Result<Vec<(u16, f64)>, Error> {
+        self.get_fee_estimates().await.map(sort_fee_estimates)
+    }
+
+    /// Get the progress of the current difficulty adjustment period, along
+    /// with an estimate of the next difficulty change.
+    ///
+    /// Requires a mempool.space backend, Blockstream's esplora doesn't serve
+    /// this endpoint.
+    #[cfg(feature = "mempool-space")]
+    pub async fn get_difficulty_adjustment(&self) -> Result<DifficultyAdjustment, Error> {
+        self.get_response_json(paths::DIFFICULTY_ADJUSTMENT).await
+    }
+
+    /// Get the feerates recommended by the server, in sat/vB, to confirm in
+    /// the next block, within half an hour, within an hour or eventually.
+    ///
+    /// Requires a mempool.space backend, Blockstream's esplora doesn't serve
+    /// this endpoint. See [`get_fee_estimates`](Self::get_fee_estimates) for
+    /// the estimates served by both.
+    #[cfg(feature = "mempool-space")]
+    pub async fn get_recommended_fees(&self) -> Result<RecommendedFees, Error> {
+        self.get_response_json(paths::RECOMMENDED_FEES).await
+    }
+
+    /// Get information about a Liquid asset given its `asset_id` in hex, or
+    /// `None` if the asset is unknown.
+    ///
+    /// Requires a Liquid-aware Esplora backend. Returns
+    /// [`Error::InvalidAssetId`] if `asset_id` isn't 64 hex characters.
+    #[cfg(feature = "elements")]
+    pub async fn get_asset(&self, asset_id: &str) -> Result<Option<AssetInfo>, Error> {
+        validate_asset_id(asset_id)?;
+        let path = paths::asset(asset_id);
+        self.get_opt_response_json(&path).await
+    }
+
+    /// Get transaction history for the specified Liquid asset, sorted with
+    /// newest first.
+    ///
+    /// Returns up to 50 mempool transactions plus the first 25 confirmed
+    /// transactions. More can be requested by specifying the last txid seen
+    /// by the previous query. Requires a Liquid-aware Esplora backend.
+    #[cfg(feature = "elements")]
+    pub async fn get_asset_txs(
+        &self,
+        asset_id: &str,
+        last_seen: Option<Txid>,
+    ) -> Result<Vec<AssetTx>, Error> {
+        validate_asset_id(asset_id)?;
+        let path = paths::asset_txs(asset_id, last_seen);
+        self.get_response_json(&path).await
+    }
+
+    /// Get the circulating supply of a Liquid asset, in its base units.
+    ///
+    /// Requires a Liquid-aware Esplora backend, which fails the request if the
+    /// asset has blinded issuances.
+    #[cfg(feature = "elements")]
+    pub async fn get_asset_supply(&self, asset_id: &str) -> Result<u64, Error> {
+        validate_asset_id(asset_id)?;
+        let path = paths::asset_supply(asset_id);
+        self.get_response_text(&path)
+            .await
+            .map(|supply| u64::from_str(supply.trim()).map_err(Error::Parsing))?
     }
 
     /// Gets some recent block summaries starting at the tip or at `height` if
@@ -437,38 +1457,228 @@ impl<S: Sleeper> AsyncClient<S> {
     ///
     /// The maximum number of summaries returned depends on the backend itself:
     /// esplora returns `10` while [mempool.space](https://mempool.space/docs/api) returns `15`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidResponse`] if the server returns no summaries,
+    /// since at least the block at `height` (or the tip) is always expected.
     pub async fn get_blocks(&self, height: Option<u32>) -> Result<Vec<BlockSummary>, Error> {
-        let path = match height {
-            Some(height) => format!("/blocks/{height}"),
-            None => "/blocks".to_string(),
-        };
+        let path = paths::blocks(height);
         let blocks: Vec<BlockSummary> = self.get_response_json(&path).await?;
         if blocks.is_empty() {
-            return Err(Error::InvalidResponse);
+            return Err(Error::InvalidResponse {
+                endpoint: path,
+                reason: "empty blocks array".to_string(),
+            });
+        }
+        Ok(blocks)
+    }
+
+    /// Gets some recent blocks starting at the tip or at `height` if provided,
+    /// with the details of [`BlockInfo`] such as their number of transactions,
+    /// size and weight.
+    ///
+    /// This is the same request as [`AsyncClient::get_blocks`], the backends
+    /// returning these details in `/blocks` too, so it costs a single request
+    /// rather than one [`AsyncClient::get_block_info`] per block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidResponse`] if the server returns no blocks,
+    /// since at least the block at `height` (or the tip) is always expected.
+    pub async fn get_blocks_detailed(&self, height: Option<u32>) -> Result<Vec<BlockInfo>, Error> {
+        let path = paths::blocks(height);
+        let blocks: Vec<BlockInfo> = self.get_response_json(&path).await?;
+        if blocks.is_empty() {
+            return Err(Error::InvalidResponse {
+                endpoint: path,
+                reason: "empty blocks array".to_string(),
+            });
         }
         Ok(blocks)
     }
 
+    /// Get the block summaries for every height from `from` to `to`
+    /// (inclusive), sorted by height.
+    ///
+    /// The range is paged through with [`AsyncClient::get_blocks`], making up
+    /// to [`DEFAULT_CONCURRENCY`] requests concurrently. The summaries reflect
+    /// the best chain at the time of each request, so a reorg happening
+    /// meanwhile may yield a mix of both chains. Requesting heights above the
+    /// current tip fails.
+    pub async fn get_blocks_range(&self, from: u32, to: u32) -> Result<Vec<BlockSummary>, Error> {
+        let pages: Vec<Vec<BlockSummary>> =
+            stream::iter((from..=to).rev().step_by(BLOCK_SUMMARIES_PAGE_SIZE))
+                .map(|height| self.get_blocks(Some(height)))
+                .buffered(DEFAULT_CONCURRENCY)
+                .try_collect()
+                .await?;
+
+        // Backends returning more than `BLOCK_SUMMARIES_PAGE_SIZE` summaries
+        // per page yield overlapping pages.
+        let mut seen = HashSet::new();
+        let mut blocks: Vec<BlockSummary> = pages
+            .into_iter()
+            .flatten()
+            .filter(|block| (from..=to).contains(&block.time.height))
+            .filter(|block| seen.insert(block.id))
+            .collect();
+        blocks.sort_by_key(|block| block.time.height);
+        Ok(blocks)
+    }
+
     /// Get the underlying base URL.
     pub fn url(&self) -> &str {
         &self.url
     }
 
+    /// Get the maximum number of times a request is retried.
+    pub fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+
     /// Get the underlying [`Client`].
     pub fn client(&self) -> &Client {
         &self.client
     }
 
-    /// Sends a GET request to the given `url`, retrying failed attempts
-    /// for retryable error codes until max retries hit.
-    async fn get_with_retry(&self, url: &str) -> Result<Response, Error> {
+    /// Sends a GET request to the given `url` with the given `headers` on top
+    /// of the client's own, retrying failed attempts for retryable error
+    /// codes until max retries hit or the total timeout is exceeded.
+    async fn get_with_retry_and_headers(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error> {
+        let headers = extra_header_map(headers)?;
+        self.send_with_retry(Method::GET, url, None, &headers, &self.retryable_codes)
+            .await
+    }
+
+    /// Sends a POST request with the given `body` to the given `url`,
+    /// retrying failed attempts like
+    /// [`get_with_retry_and_headers`](Self::get_with_retry_and_headers).
+    ///
+    /// A `400 Bad Request` is never retried, even if configured as retryable:
+    /// it is how Esplora reports that its node rejected a transaction, which
+    /// won't succeed on a later attempt.
+    async fn post_with_retry(
+        &self,
+        url: &str,
+        body: &str,
+        headers: &header::HeaderMap,
+    ) -> Result<Response, Error> {
+        let retryable_codes: Vec<u16> = self
+            .retryable_codes
+            .iter()
+            .copied()
+            .filter(|&code| code != 400)
+            .collect();
+        self.send_with_retry(Method::POST, url, Some(body), headers, &retryable_codes)
+            .await
+    }
+
+    /// Sends a request with the given `method` and `body` to the given `url`,
+    /// retrying failed attempts for the given `retryable_codes` until max
+    /// retries hit or the total timeout is exceeded.
+    async fn send_with_retry(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&str>,
+        headers: &header::HeaderMap,
+        retryable_codes: &[u16],
+    ) -> Result<Response, Error> {
+        let total_timeout = match self.total_timeout {
+            Some(total_timeout) => total_timeout,
+            None => {
+                return self
+                    .send_with_retry_unbounded(method, url, body, headers, retryable_codes)
+                    .await
+            }
+        };
+        let request =
+            self.send_with_retry_unbounded(method.clone(), url, body, headers, retryable_codes);
+        let timeout = self.sleep(total_timeout);
+        futures::pin_mut!(request, timeout);
+        match future::select(request, timeout).await {
+            Either::Left((resp, _)) => resp,
+            Either::Right(((), _)) => {
+                debug!("{} {} timed out after {:?}", method, url, total_timeout);
+                Err(Error::Timeout)
+            }
+        }
+    }
+
+    /// Sends a request with the given `method` and `body` to the given `url`,
+    /// retrying failed attempts for the given `retryable_codes` until max
+    /// retries hit, without bounding the total time spent.
+    async fn send_with_retry_unbounded(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&str>,
+        headers: &header::HeaderMap,
+        retryable_codes: &[u16],
+    ) -> Result<Response, Error> {
         let mut delay = BASE_BACKOFF_MILLIS;
         let mut attempts = 0;
 
+        let path = url.strip_prefix(self.url.as_str()).unwrap_or(url);
+
         loop {
-            match self.client.get(url).send().await? {
-                resp if attempts < self.max_retries && is_status_retryable(resp.status()) => {
-                    S::sleep(delay).await;
+            trace!("{} {}", method, url);
+            let start = Instant::now();
+            let mut request = self
+                .client
+                .request(method.clone(), url)
+                .headers(self.headers.clone())
+                .headers(headers.clone());
+            if let Some(body) = body {
+                request = request.body(body.to_string());
+            }
+            let request = request.send();
+            #[cfg(feature = "tracing")]
+            let span = crate::request_span(path, attempts);
+            #[cfg(feature = "tracing")]
+            let request = tracing::Instrument::instrument(request, span.clone());
+            let resp = request.await;
+            let elapsed = start.elapsed();
+            let status = resp.as_ref().ok().map(|resp| resp.status().as_u16());
+            #[cfg(feature = "tracing")]
+            crate::record_response(&span, status, elapsed);
+            if let Some(on_request) = &self.on_request {
+                on_request.observe(path, elapsed, status);
+            }
+
+            match resp? {
+                resp if attempts < self.max_retries()
+                    && is_status_retryable(resp.status(), retryable_codes) =>
+                {
+                    let retry_after = resp
+                        .headers()
+                        .get(header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(retry_after_delay);
+                    let backoff = if self.retry_jitter {
+                        jitter(delay)
+                    } else {
+                        delay
+                    };
+                    let wait = retry_after.unwrap_or(backoff);
+                    debug!(
+                        "HTTP {} response from {}, retrying in {:?} (retry {} of {})",
+                        resp.status().as_u16(),
+                        url,
+                        wait,
+                        attempts + 1,
+                        self.max_retries()
+                    );
+                    // Release the connection of the failed attempt rather than
+                    // holding it for the whole delay, or until the future is
+                    // dropped if it is cancelled while sleeping.
+                    drop(resp);
+                    self.sleep(wait).await;
                     attempts += 1;
                     delay *= 2;
                 }
@@ -478,8 +1688,203 @@ impl<S: Sleeper> AsyncClient<S> {
     }
 }
 
-fn is_status_retryable(status: reqwest::StatusCode) -> bool {
-    RETRYABLE_ERROR_CODES.contains(&status.as_u16())
+/// Future returned by the methods of [`EsploraApi`].
+///
+/// It is `Send` except when targeting `wasm32`, where the futures of
+/// [`reqwest`] aren't.
+#[cfg(not(target_arch = "wasm32"))]
+pub type ApiFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>;
+/// Future returned by the methods of [`EsploraApi`].
+///
+/// It is `Send` except when targeting `wasm32`, where the futures of
+/// [`reqwest`] aren't.
+#[cfg(target_arch = "wasm32")]
+pub type ApiFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + 'a>>;
+
+/// The common read methods of an async Esplora client, along with
+/// [`broadcast`](EsploraApi::broadcast), to write code that is generic over
+/// the client, e.g. to substitute a mock in tests.
+///
+/// As async functions can't be used in traits with this crate's MSRV, the
+/// methods return boxed futures. See [`AsyncClient`] for the documentation
+/// of each method. The blocking client implements the equivalent
+/// [`BlockingEsploraApi`](crate::BlockingEsploraApi) instead.
+pub trait EsploraApi {
+    /// Get a [`Transaction`] option given its [`Txid`].
+    fn get_tx<'a>(&'a self, txid: &'a Txid) -> ApiFuture<'a, Option<Transaction>>;
+
+    /// Get transaction info given its [`Txid`].
+    fn get_tx_info<'a>(&'a self, txid: &'a Txid) -> ApiFuture<'a, Option<Tx>>;
+
+    /// Get the status of a [`Transaction`] given its [`Txid`].
+    fn get_tx_status<'a>(&'a self, txid: &'a Txid) -> ApiFuture<'a, TxStatus>;
+
+    /// Get the spending status of an output given a [`Txid`] and the output
+    /// index.
+    fn get_output_status<'a>(
+        &'a self,
+        txid: &'a Txid,
+        index: u64,
+    ) -> ApiFuture<'a, Option<OutputStatus>>;
+
+    /// Get the height of the current blockchain tip.
+    fn get_height(&self) -> ApiFuture<'_, u32>;
+
+    /// Get the [`BlockHash`] of the current blockchain tip.
+    fn get_tip_hash(&self) -> ApiFuture<'_, BlockHash>;
+
+    /// Get the [`BlockHash`] of a specific block height.
+    fn get_block_hash(&self, block_height: u32) -> ApiFuture<'_, BlockHash>;
+
+    /// Get a [`BlockHeader`] given a particular block hash.
+    fn get_header_by_hash<'a>(&'a self, block_hash: &'a BlockHash) -> ApiFuture<'a, BlockHeader>;
+
+    /// Get the [`BlockStatus`] given a particular [`BlockHash`].
+    fn get_block_status<'a>(&'a self, block_hash: &'a BlockHash) -> ApiFuture<'a, BlockStatus>;
+
+    /// Get confirmed transaction history for the specified address/scripthash,
+    /// sorted with newest first.
+    fn scripthash_txs<'a>(
+        &'a self,
+        script: &'a Script,
+        last_seen: Option<Txid>,
+    ) -> ApiFuture<'a, Vec<Tx>>;
+
+    /// Get a map where the key is the confirmation target (in number of
+    /// blocks) and the value is the estimated feerate (in sat/vB).
+    fn get_fee_estimates(&self) -> ApiFuture<'_, HashMap<u16, f64>>;
+
+    /// Broadcast a [`Transaction`] to Esplora.
+    fn broadcast<'a>(&'a self, transaction: &'a Transaction) -> ApiFuture<'a, ()>;
+}
+
+/// Implement [`EsploraApi`] for [`AsyncClient`] with the given bounds on its
+/// [`Sleeper`], which differ when the futures need to be `Send`.
+macro_rules! impl_esplora_api {
+    ($($bounds:tt)*) => {
+        impl<S> EsploraApi for AsyncClient<S>
+        where
+            $($bounds)*
+        {
+            fn get_tx<'a>(&'a self, txid: &'a Txid) -> ApiFuture<'a, Option<Transaction>> {
+                Box::pin(AsyncClient::get_tx(self, txid))
+            }
+
+            fn get_tx_info<'a>(&'a self, txid: &'a Txid) -> ApiFuture<'a, Option<Tx>> {
+                Box::pin(AsyncClient::get_tx_info(self, txid))
+            }
+
+            fn get_tx_status<'a>(&'a self, txid: &'a Txid) -> ApiFuture<'a, TxStatus> {
+                Box::pin(AsyncClient::get_tx_status(self, txid))
+            }
+
+            fn get_output_status<'a>(
+                &'a self,
+                txid: &'a Txid,
+                index: u64,
+            ) -> ApiFuture<'a, Option<OutputStatus>> {
+                Box::pin(AsyncClient::get_output_status(self, txid, index))
+            }
+
+            fn get_height(&self) -> ApiFuture<'_, u32> {
+                Box::pin(AsyncClient::get_height(self))
+            }
+
+            fn get_tip_hash(&self) -> ApiFuture<'_, BlockHash> {
+                Box::pin(AsyncClient::get_tip_hash(self))
+            }
+
+            fn get_block_hash(&self, block_height: u32) -> ApiFuture<'_, BlockHash> {
+                Box::pin(AsyncClient::get_block_hash(self, block_height))
+            }
+
+            fn get_header_by_hash<'a>(
+                &'a self,
+                block_hash: &'a BlockHash,
+            ) -> ApiFuture<'a, BlockHeader> {
+                Box::pin(AsyncClient::get_header_by_hash(self, block_hash))
+            }
+
+            fn get_block_status<'a>(
+                &'a self,
+                block_hash: &'a BlockHash,
+            ) -> ApiFuture<'a, BlockStatus> {
+                Box::pin(AsyncClient::get_block_status(self, block_hash))
+            }
+
+            fn scripthash_txs<'a>(
+                &'a self,
+                script: &'a Script,
+                last_seen: Option<Txid>,
+            ) -> ApiFuture<'a, Vec<Tx>> {
+                Box::pin(AsyncClient::scripthash_txs(self, script, last_seen))
+            }
+
+            fn get_fee_estimates(&self) -> ApiFuture<'_, HashMap<u16, f64>> {
+                Box::pin(AsyncClient::get_fee_estimates(self))
+            }
+
+            fn broadcast<'a>(&'a self, transaction: &'a Transaction) -> ApiFuture<'a, ()> {
+                Box::pin(AsyncClient::broadcast(self, transaction))
+            }
+        }
+    };
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl_esplora_api!(S: Sleeper + Send + Sync, S::Sleep: Send);
+#[cfg(target_arch = "wasm32")]
+impl_esplora_api!(S: Sleeper);
+
+/// The fields of a [`Tx`] needed to find the next page of a transaction
+/// history, much cheaper to parse than the whole transaction.
+#[derive(serde::Deserialize)]
+struct PagedTx {
+    txid: Txid,
+    status: PagedTxStatus,
+}
+
+/// The confirmation status of a [`PagedTx`].
+#[derive(serde::Deserialize)]
+struct PagedTxStatus {
+    confirmed: bool,
+}
+
+/// Parse a page of transaction history for the specified address.
+fn parse_address_txs<T: serde::de::DeserializeOwned>(
+    address: &Address,
+    body: &[u8],
+) -> Result<T, Error> {
+    serde_json::from_slice(body).map_err(|e| Error::InvalidResponse {
+        endpoint: paths::address_txs(address, None),
+        reason: e.to_string(),
+    })
+}
+
+/// Convert the `headers` of a [`Builder`] into a validated [`header::HeaderMap`].
+fn header_map(headers: HashMap<String, String>) -> Result<header::HeaderMap, Error> {
+    let mut header_map = header::HeaderMap::new();
+    for (k, v) in headers {
+        let (header_name, header_value) = parse_header(&k, &v)?;
+        header_map.insert(header_name, header_value);
+    }
+    Ok(header_map)
+}
+
+/// Convert the `headers` set on a single request into a validated
+/// [`header::HeaderMap`], to be set after the client's own so it overrides
+/// them.
+fn extra_header_map(headers: &[(&str, &str)]) -> Result<header::HeaderMap, Error> {
+    header_map(
+        headers
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+    )
+}
+
+fn is_status_retryable(status: reqwest::StatusCode, retryable_codes: &[u16]) -> bool {
+    retryable_codes.contains(&status.as_u16())
 }
 
 pub trait Sleeper: 'static {
@@ -490,6 +1895,50 @@ pub trait Sleeper: 'static {
 #[derive(Debug, Clone, Copy)]
 pub struct DefaultSleeper;
 
+/// Future returned by the sleep function of a client built with
+/// [`Builder::build_async_with_boxed_sleeper`].
+///
+/// It is `Send` except when targeting `wasm32`, like [`ApiFuture`].
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxedSleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+/// Future returned by the sleep function of a client built with
+/// [`Builder::build_async_with_boxed_sleeper`].
+///
+/// It is `Send` except when targeting `wasm32`, like [`ApiFuture`].
+#[cfg(target_arch = "wasm32")]
+pub type BoxedSleep = Pin<Box<dyn Future<Output = ()>>>;
+
+/// [`Sleeper`] of the clients built with
+/// [`Builder::build_async_with_boxed_sleeper`], which sleep with the function
+/// given there instead.
+///
+/// # Panics
+///
+/// It can't sleep on its own, so a client built with it in any other way,
+/// e.g. through [`AsyncClient::from_builder`], panics the first time it has to
+/// wait before a retry or poll.
+#[derive(Debug, Clone, Copy)]
+pub struct BoxedSleeper;
+
+impl Sleeper for BoxedSleeper {
+    type Sleep = BoxedSleep;
+
+    fn sleep(_dur: std::time::Duration) -> Self::Sleep {
+        panic!("a client with a `BoxedSleeper` must be built with `build_async_with_boxed_sleeper`")
+    }
+}
+
+/// Sleep function of an [`AsyncClient`], see
+/// [`Builder::build_async_with_boxed_sleeper`].
+#[derive(Clone)]
+struct SleepFn(Arc<dyn Fn(Duration) -> BoxedSleep + Send + Sync>);
+
+impl std::fmt::Debug for SleepFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SleepFn")
+    }
+}
+
 #[cfg(any(test, feature = "tokio"))]
 impl Sleeper for DefaultSleeper {
     type Sleep = tokio::time::Sleep;
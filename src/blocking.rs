@@ -11,29 +11,41 @@
 
 //! Esplora by way of `minreq` HTTP client.
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::str::FromStr;
 use std::thread;
+use std::time::Duration;
 
-#[allow(unused_imports)]
-use log::{debug, error, info, trace};
+use log::{debug, trace};
 
-use minreq::{Proxy, Request, Response};
+use minreq::{Method, Proxy, Request, Response};
 
 use bitcoin::consensus::{deserialize, serialize, Decodable};
-use bitcoin::hashes::{sha256, Hash};
+use bitcoin::hashes::sha256;
 use bitcoin::hex::{DisplayHex, FromHex};
-use bitcoin::Address;
 use bitcoin::{
-    block::Header as BlockHeader, Block, BlockHash, MerkleBlock, Script, Transaction, Txid,
+    block::Header as BlockHeader, Block, BlockHash, MerkleBlock, OutPoint, Script, Transaction,
+    Txid,
 };
+use bitcoin::{Address, Network};
 
 use crate::api::AddressStats;
+use crate::Instant;
 use crate::{
-    BlockStatus, BlockSummary, Builder, Error, MerkleProof, OutputStatus, Tx, TxStatus,
-    BASE_BACKOFF_MILLIS, RETRYABLE_ERROR_CODES,
+    byte_range, check_header_chain, http_response_error, idempotency_key, jitter,
+    network_from_genesis_hash, next_last_seen, parse_header, paths, retry_after_delay, slice_range,
+    sort_fee_estimates, trim_base_url, validate_address_prefix, validate_base_url, BlockInfo,
+    BlockStatus, BlockSummary, Builder, Error, FeeCache, MempoolRecentTx, MempoolStats,
+    MerkleProof, OutputStatus, RedirectPolicy, RequestFormat, RequestObserver, ScriptHashStats, Tx,
+    TxStatus, TxWithSpends, Utxo, UtxoFilter, BASE_BACKOFF_MILLIS, DEFAULT_USER_AGENT,
+    IDEMPOTENCY_KEY_HEADER,
 };
+#[cfg(feature = "elements")]
+use crate::{validate_asset_id, AssetInfo, AssetTx};
+#[cfg(feature = "mempool-space")]
+use crate::{DifficultyAdjustment, RecommendedFees};
 
 #[derive(Debug, Clone)]
 pub struct BlockingClient {
@@ -47,157 +59,272 @@ pub struct BlockingClient {
     pub headers: HashMap<String, String>,
     /// Number of times to retry a request
     pub max_retries: usize,
+    /// Whether to randomize the backoff delay between retries
+    pub retry_jitter: bool,
+    /// Response status codes for which a request is retried
+    pub retryable_codes: Vec<u16>,
+    /// `User-Agent` header to send with every request
+    pub user_agent: Option<String>,
+    /// The proxy and headers prepared when the client was built, unless they
+    /// were invalid.
+    request_options: Option<RequestOptions>,
+    /// The fee estimates cache, if enabled.
+    fee_cache: Option<FeeCache>,
+    /// Callback invoked after each request attempt, if any.
+    on_request: Option<RequestObserver>,
+    /// Bound on the whole sequence of attempts of a request, if any.
+    total_timeout: Option<Duration>,
+    /// Whether to send an idempotency key with each broadcast.
+    idempotency: bool,
+    /// How redirects are followed, `minreq`'s default if `None`.
+    redirect_policy: Option<RedirectPolicy>,
 }
 
 impl BlockingClient {
     /// Build a blocking client from a [`Builder`]
+    ///
+    /// An invalid proxy or header set on the [`Builder`] is reported by every
+    /// request made with the client, see [`BlockingClient::try_from_builder`]
+    /// to report it right away instead.
     pub fn from_builder(builder: Builder) -> Self {
+        let request_options = RequestOptions::new(
+            builder.proxy.as_ref(),
+            &builder.headers,
+            builder.user_agent.as_ref(),
+        )
+        .ok();
+
         Self {
-            url: builder.base_url,
+            url: trim_base_url(builder.base_url),
             proxy: builder.proxy,
             timeout: builder.timeout,
             headers: builder.headers,
             max_retries: builder.max_retries,
+            retry_jitter: builder.retry_jitter,
+            retryable_codes: builder.retryable_codes,
+            user_agent: builder.user_agent,
+            request_options,
+            fee_cache: builder
+                .fee_cache_ttl
+                .map(|ttl| FeeCache::new(ttl, builder.on_cache.clone())),
+            on_request: builder.on_request,
+            total_timeout: builder.total_timeout,
+            idempotency: builder.idempotency,
+            redirect_policy: builder.redirect_policy,
         }
     }
 
+    /// Build a blocking client from a [`Builder`], validating its base URL,
+    /// proxy and HTTP headers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidBaseUrl`] if the base URL isn't an absolute
+    /// `http` or `https` URL, [`Error::Minreq`] if the proxy is invalid, or
+    /// [`Error::InvalidHttpHeaderName`] or [`Error::InvalidHttpHeaderValue`]
+    /// if any of the headers set on the [`Builder`] is invalid.
+    pub fn try_from_builder(builder: Builder) -> Result<Self, Error> {
+        validate_base_url(&builder.base_url)?;
+        let client = Self::from_builder(builder);
+        client.request_options()?;
+        Ok(client)
+    }
+
     /// Get the underlying base URL.
     pub fn url(&self) -> &str {
         &self.url
     }
 
+    /// Get the maximum number of times a request is retried.
+    pub fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+
     /// Perform a raw HTTP GET request with the given URI `path`.
     pub fn get_request(&self, path: &str) -> Result<Request, Error> {
-        let mut request = minreq::get(format!("{}{}", self.url, path));
+        self.prepare_request(minreq::get(format!("{}{}", self.url, path)), &[])
+    }
 
-        if let Some(proxy) = &self.proxy {
-            let proxy = Proxy::new(proxy.as_str())?;
-            request = request.with_proxy(proxy);
+    /// Set the client's proxy, timeout and headers on `request`, along with
+    /// the given `headers`, which take precedence over the client's own.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Minreq`] if the client's proxy is invalid, or
+    /// [`Error::InvalidHttpHeaderName`] or [`Error::InvalidHttpHeaderValue`]
+    /// if any of its headers is.
+    fn prepare_request(
+        &self,
+        mut request: Request,
+        headers: &[(&str, &str)],
+    ) -> Result<Request, Error> {
+        let options = self.request_options()?;
+
+        if let Some(proxy) = &options.proxy {
+            request = request.with_proxy(proxy.clone());
         }
 
         if let Some(timeout) = &self.timeout {
             request = request.with_timeout(*timeout);
         }
 
-        if !self.headers.is_empty() {
-            for (key, value) in &self.headers {
-                request = request.with_header(key, value);
-            }
+        match self.redirect_policy {
+            Some(RedirectPolicy::None) => request = request.with_follow_redirects(false),
+            Some(RedirectPolicy::Limited(max)) => request = request.with_max_redirects(max),
+            None => {}
         }
 
-        Ok(request)
+        if headers.is_empty() {
+            request = request.with_headers(options.headers.iter().map(|(k, v)| (k, v)));
+        } else {
+            request = request.with_headers(
+                options
+                    .headers
+                    .iter()
+                    .filter(|(key, _)| {
+                        !headers
+                            .iter()
+                            .any(|(name, _)| name.eq_ignore_ascii_case(key))
+                    })
+                    .map(|(k, v)| (k, v)),
+            );
+        }
+        Ok(request.with_headers(headers.iter().copied()))
     }
 
-    fn get_opt_response<T: Decodable>(&self, path: &str) -> Result<Option<T>, Error> {
-        match self.get_with_retry(path) {
-            Ok(resp) if is_status_not_found(resp.status_code) => Ok(None),
+    /// Get the proxy and headers to set on every request, prepared when the
+    /// client was built unless its public fields were changed since.
+    fn request_options(&self) -> Result<Cow<'_, RequestOptions>, Error> {
+        match &self.request_options {
+            Some(options) if options.is_prepared_from(self) => Ok(Cow::Borrowed(options)),
+            _ => RequestOptions::new(self.proxy.as_ref(), &self.headers, self.user_agent.as_ref())
+                .map(Cow::Owned),
+        }
+    }
+
+    /// Make an HTTP GET request to the given `path` for a body in the given
+    /// `format`, returning the response if its status is `200 OK`.
+    fn get_response_with_format(
+        &self,
+        path: &str,
+        format: RequestFormat,
+    ) -> Result<Response, Error> {
+        match self.get_with_retry_and_headers(path, format.headers()) {
             Ok(resp) if !is_status_ok(resp.status_code) => {
                 let status = u16::try_from(resp.status_code).map_err(Error::StatusCode)?;
                 let message = resp.as_str().unwrap_or_default().to_string();
-                Err(Error::HttpResponse { status, message })
+                Err(http_response_error(status, message))
             }
-            Ok(resp) => Ok(Some(deserialize::<T>(resp.as_bytes())?)),
+            Ok(resp) => Ok(resp),
             Err(e) => Err(e),
         }
     }
 
+    /// Same as [`get_response_with_format`](Self::get_response_with_format),
+    /// returning `None` if the status is `404 Not Found`.
+    fn get_opt_response_with_format(
+        &self,
+        path: &str,
+        format: RequestFormat,
+    ) -> Result<Option<Response>, Error> {
+        match self.get_response_with_format(path, format) {
+            Ok(resp) => Ok(Some(resp)),
+            Err(Error::HttpResponse { status: 404, .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn get_opt_response<T: Decodable>(&self, path: &str) -> Result<Option<T>, Error> {
+        match self.get_opt_response_with_format(path, RequestFormat::Path)? {
+            Some(resp) => Ok(Some(deserialize::<T>(resp.as_bytes())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_opt_response_bytes(&self, path: &str) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self
+            .get_opt_response_with_format(path, RequestFormat::Path)?
+            .map(Response::into_bytes))
+    }
+
     fn get_opt_response_txid(&self, path: &str) -> Result<Option<Txid>, Error> {
-        match self.get_with_retry(path) {
-            Ok(resp) if is_status_not_found(resp.status_code) => Ok(None),
-            Ok(resp) if !is_status_ok(resp.status_code) => {
-                let status = u16::try_from(resp.status_code).map_err(Error::StatusCode)?;
-                let message = resp.as_str().unwrap_or_default().to_string();
-                Err(Error::HttpResponse { status, message })
-            }
-            Ok(resp) => Ok(Some(
+        match self.get_opt_response_with_format(path, RequestFormat::Path)? {
+            Some(resp) => Ok(Some(
                 Txid::from_str(resp.as_str().map_err(Error::Minreq)?).map_err(Error::HexToArray)?,
             )),
-            Err(e) => Err(e),
+            None => Ok(None),
         }
     }
 
     fn get_opt_response_hex<T: Decodable>(&self, path: &str) -> Result<Option<T>, Error> {
-        match self.get_with_retry(path) {
-            Ok(resp) if is_status_not_found(resp.status_code) => Ok(None),
-            Ok(resp) if !is_status_ok(resp.status_code) => {
-                let status = u16::try_from(resp.status_code).map_err(Error::StatusCode)?;
-                let message = resp.as_str().unwrap_or_default().to_string();
-                Err(Error::HttpResponse { status, message })
-            }
-            Ok(resp) => {
+        match self.get_opt_response_with_format(path, RequestFormat::Path)? {
+            Some(resp) => {
                 let hex_str = resp.as_str().map_err(Error::Minreq)?;
-                let hex_vec = Vec::from_hex(hex_str).unwrap();
+                let hex_vec = Vec::from_hex(hex_str)?;
                 deserialize::<T>(&hex_vec)
                     .map_err(Error::BitcoinEncoding)
                     .map(|r| Some(r))
             }
-            Err(e) => Err(e),
+            None => Ok(None),
         }
     }
 
     fn get_response_hex<T: Decodable>(&self, path: &str) -> Result<T, Error> {
-        match self.get_with_retry(path) {
-            Ok(resp) if !is_status_ok(resp.status_code) => {
-                let status = u16::try_from(resp.status_code).map_err(Error::StatusCode)?;
-                let message = resp.as_str().unwrap_or_default().to_string();
-                Err(Error::HttpResponse { status, message })
-            }
-            Ok(resp) => {
-                let hex_str = resp.as_str().map_err(Error::Minreq)?;
-                let hex_vec = Vec::from_hex(hex_str).unwrap();
-                deserialize::<T>(&hex_vec).map_err(Error::BitcoinEncoding)
-            }
-            Err(e) => Err(e),
-        }
+        let resp = self.get_response_with_format(path, RequestFormat::Path)?;
+        let hex_str = resp.as_str().map_err(Error::Minreq)?;
+        let hex_vec = Vec::from_hex(hex_str)?;
+        deserialize::<T>(&hex_vec).map_err(Error::BitcoinEncoding)
     }
 
     fn get_response_json<'a, T: serde::de::DeserializeOwned>(
         &'a self,
         path: &'a str,
     ) -> Result<T, Error> {
-        let response = self.get_with_retry(path);
-        match response {
-            Ok(resp) if !is_status_ok(resp.status_code) => {
-                let status = u16::try_from(resp.status_code).map_err(Error::StatusCode)?;
-                let message = resp.as_str().unwrap_or_default().to_string();
-                Err(Error::HttpResponse { status, message })
-            }
-            Ok(resp) => Ok(resp.json::<T>().map_err(Error::Minreq)?),
-            Err(e) => Err(e),
-        }
+        let resp = self.get_response_with_format(path, RequestFormat::Path)?;
+        resp.json::<T>().map_err(Error::Minreq)
     }
 
     fn get_opt_response_json<T: serde::de::DeserializeOwned>(
         &self,
         path: &str,
     ) -> Result<Option<T>, Error> {
-        match self.get_with_retry(path) {
-            Ok(resp) if is_status_not_found(resp.status_code) => Ok(None),
-            Ok(resp) if !is_status_ok(resp.status_code) => {
-                let status = u16::try_from(resp.status_code).map_err(Error::StatusCode)?;
-                let message = resp.as_str().unwrap_or_default().to_string();
-                Err(Error::HttpResponse { status, message })
-            }
-            Ok(resp) => Ok(Some(resp.json::<T>()?)),
-            Err(e) => Err(e),
+        match self.get_opt_response_with_format(path, RequestFormat::Path)? {
+            Some(resp) => Ok(Some(resp.json::<T>()?)),
+            None => Ok(None),
         }
     }
 
-    fn get_response_str(&self, path: &str) -> Result<String, Error> {
-        match self.get_with_retry(path) {
-            Ok(resp) if !is_status_ok(resp.status_code) => {
-                let status = u16::try_from(resp.status_code).map_err(Error::StatusCode)?;
-                let message = resp.as_str().unwrap_or_default().to_string();
-                Err(Error::HttpResponse { status, message })
-            }
-            Ok(resp) => Ok(resp.as_str()?.to_string()),
-            Err(e) => Err(e),
+    fn get_opt_response_str(&self, path: &str) -> Result<Option<String>, Error> {
+        match self.get_opt_response_with_format(path, RequestFormat::Path)? {
+            Some(resp) => Ok(Some(resp.as_str()?.to_string())),
+            None => Ok(None),
         }
     }
 
+    fn get_response_str(&self, path: &str) -> Result<String, Error> {
+        let resp = self.get_response_with_format(path, RequestFormat::Path)?;
+        Ok(resp.as_str()?.to_string())
+    }
+
     /// Get a [`Transaction`] option given its [`Txid`]
     pub fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, Error> {
-        self.get_opt_response(&format!("/tx/{}/raw", txid))
+        self.get_opt_response(&paths::tx_raw(txid))
+    }
+
+    /// Get the hex-encoded raw [`Transaction`] option given its [`Txid`],
+    /// exactly as returned by the server.
+    pub fn get_tx_hex(&self, txid: &Txid) -> Result<Option<String>, Error> {
+        self.get_opt_response_str(&paths::tx_hex(txid))
+    }
+
+    /// Get the raw [`Transaction`] option given its [`Txid`], as the exact
+    /// bytes returned by the server.
+    ///
+    /// Unlike [`get_tx`](Self::get_tx), the bytes aren't deserialized, so
+    /// they can be relayed or hashed without any risk of being serialized
+    /// differently.
+    pub fn get_tx_raw_bytes(&self, txid: &Txid) -> Result<Option<Vec<u8>>, Error> {
+        self.get_opt_response_bytes(&paths::tx_raw(txid))
     }
 
     /// Get a [`Transaction`] given its [`Txid`].
@@ -216,44 +343,186 @@ impl BlockingClient {
         block_hash: &BlockHash,
         index: usize,
     ) -> Result<Option<Txid>, Error> {
-        self.get_opt_response_txid(&format!("/block/{}/txid/{}", block_hash, index))
+        self.get_opt_response_txid(&paths::block_txid(block_hash, index))
+    }
+
+    /// Get the coinbase [`Transaction`] of the block with the given hash, or
+    /// `None` if the block is unknown.
+    ///
+    /// The coinbase is always the first transaction of a block, so this looks
+    /// up its [`Txid`] at index `0` with
+    /// [`get_txid_at_block_index`](Self::get_txid_at_block_index), then fetches
+    /// it with [`get_tx`](Self::get_tx).
+    pub fn get_block_coinbase(&self, block_hash: &BlockHash) -> Result<Option<Transaction>, Error> {
+        match self.get_txid_at_block_index(block_hash, 0)? {
+            Some(txid) => self.get_tx(&txid),
+            None => Ok(None),
+        }
     }
 
     /// Get the status of a [`Transaction`] given its [`Txid`].
     pub fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, Error> {
-        self.get_response_json(&format!("/tx/{}/status", txid))
+        self.get_response_json(&paths::tx_status(txid))
     }
 
     /// Get transaction info given it's [`Txid`].
     pub fn get_tx_info(&self, txid: &Txid) -> Result<Option<Tx>, Error> {
-        self.get_opt_response_json(&format!("/tx/{}", txid))
+        self.get_opt_response_json(&paths::tx(txid))
+    }
+
+    /// Get the number of confirmations of a [`Transaction`] given its
+    /// [`Txid`], `0` if it is in the mempool and `None` if it is unknown.
+    pub fn get_tx_confirmations(&self, txid: &Txid) -> Result<Option<u32>, Error> {
+        let tx = match self.get_tx_info(txid)? {
+            Some(tx) => tx,
+            None => return Ok(None),
+        };
+        let tip_height = self.get_height()?;
+        Ok(Some(tx.status.confirmations(tip_height)))
     }
 
     /// Get a [`BlockHeader`] given a particular block hash.
     pub fn get_header_by_hash(&self, block_hash: &BlockHash) -> Result<BlockHeader, Error> {
-        self.get_response_hex(&format!("/block/{}/header", block_hash))
+        self.get_response_hex(&paths::block_header(block_hash))
+    }
+
+    /// Get a [`BlockHeader`] given a particular block height.
+    ///
+    /// This resolves the [`BlockHash`] at `height` first, so it takes two
+    /// requests. Returns [`Error::HeaderHeightNotFound`] if `height` is above
+    /// the current tip.
+    pub fn get_header_by_height(&self, height: u32) -> Result<BlockHeader, Error> {
+        let block_hash = self.get_block_hash_at(height)?;
+        self.get_header_by_hash(&block_hash)
+    }
+
+    /// Get the [`BlockHeader`]s of the `count` blocks starting at
+    /// `start_height`, along with their heights.
+    ///
+    /// The block hashes are resolved first, then the headers are fetched, so
+    /// this takes `2 * count` requests.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidResponse`] if a header doesn't build on the
+    /// previous one, e.g. because the chain was reorganized while fetching
+    /// them, and [`Error::HeaderHeightNotFound`] if a height is above the
+    /// current tip.
+    pub fn get_headers(
+        &self,
+        start_height: u32,
+        count: u32,
+    ) -> Result<Vec<(u32, BlockHeader)>, Error> {
+        let heights = start_height..start_height.saturating_add(count);
+        let block_hashes = heights
+            .clone()
+            .map(|height| self.get_block_hash_at(height))
+            .collect::<Result<Vec<_>, _>>()?;
+        let headers = heights
+            .zip(&block_hashes)
+            .map(|(height, block_hash)| Ok((height, self.get_header_by_hash(block_hash)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        check_header_chain(&headers)?;
+        Ok(headers)
+    }
+
+    /// Get the [`BlockHash`] at `height`, mapping a `404` response to
+    /// [`Error::HeaderHeightNotFound`].
+    fn get_block_hash_at(&self, height: u32) -> Result<BlockHash, Error> {
+        match self.get_block_hash(height) {
+            Err(Error::HttpResponse { status: 404, .. }) => {
+                Err(Error::HeaderHeightNotFound(height))
+            }
+            result => result,
+        }
     }
 
     /// Get the [`BlockStatus`] given a particular [`BlockHash`].
     pub fn get_block_status(&self, block_hash: &BlockHash) -> Result<BlockStatus, Error> {
-        self.get_response_json(&format!("/block/{}/status", block_hash))
+        self.get_response_json(&paths::block_status(block_hash))
+    }
+
+    /// Get the [`BlockInfo`] of a block given its [`BlockHash`], `None` if
+    /// the block isn't known.
+    pub fn get_block_info(&self, block_hash: &BlockHash) -> Result<Option<BlockInfo>, Error> {
+        self.get_opt_response_json(&paths::block(block_hash))
     }
 
     /// Get a [`Block`] given a particular [`BlockHash`].
     pub fn get_block_by_hash(&self, block_hash: &BlockHash) -> Result<Option<Block>, Error> {
-        self.get_opt_response(&format!("/block/{}/raw", block_hash))
+        self.get_opt_response(&paths::block_raw(block_hash))
+    }
+
+    /// Get `len` bytes of the raw [`Block`] given its [`BlockHash`], starting
+    /// at byte `start` of its serialization.
+    ///
+    /// This allows downloading a large block in slices rather than at once,
+    /// see [`get_block_by_hash`](Self::get_block_by_hash) to get it whole.
+    /// The range is requested with an HTTP `Range` header, which Esplora
+    /// itself ignores: only servers (or proxies in front of them) supporting
+    /// range requests save the download of the whole block, which is
+    /// otherwise sliced once received. Fewer than `len` bytes are returned if
+    /// the block ends before, none if it ends before `start`.
+    pub fn get_block_raw_range(
+        &self,
+        block_hash: &BlockHash,
+        start: usize,
+        len: usize,
+    ) -> Result<Vec<u8>, Error> {
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        let range = byte_range(start, len);
+        let resp =
+            self.get_with_retry_and_headers(&paths::block_raw(block_hash), &[("Range", &range)])?;
+
+        match resp.status_code {
+            206 => {
+                let mut bytes = resp.into_bytes();
+                bytes.truncate(len);
+                Ok(bytes)
+            }
+            200 => Ok(slice_range(resp.as_bytes(), start, len)),
+            416 => Ok(Vec::new()),
+            status => {
+                let status = u16::try_from(status).map_err(Error::StatusCode)?;
+                let message = resp.as_str().unwrap_or_default().to_string();
+                Err(http_response_error(status, message))
+            }
+        }
+    }
+
+    /// Get all the [`Txid`]s of the transactions in a [`Block`], in block
+    /// order, given its [`BlockHash`].
+    pub fn get_block_txids(&self, block_hash: &BlockHash) -> Result<Vec<Txid>, Error> {
+        self.get_response_json(&paths::block_txids(block_hash))
+    }
+
+    /// Get up to 25 [`Tx`]s from a [`Block`] given its [`BlockHash`],
+    /// beginning at `start_index` (starts from 0 if `start_index` is `None`).
+    ///
+    /// The `start_index` value MUST be a multiple of 25,
+    /// else an error will be returned by Esplora.
+    pub fn get_block_txs(
+        &self,
+        block_hash: &BlockHash,
+        start_index: Option<usize>,
+    ) -> Result<Vec<Tx>, Error> {
+        let path = paths::block_txs(block_hash, start_index);
+
+        self.get_response_json(&path)
     }
 
     /// Get a merkle inclusion proof for a [`Transaction`] with the given
     /// [`Txid`].
     pub fn get_merkle_proof(&self, txid: &Txid) -> Result<Option<MerkleProof>, Error> {
-        self.get_opt_response_json(&format!("/tx/{}/merkle-proof", txid))
+        self.get_opt_response_json(&paths::tx_merkle_proof(txid))
     }
 
     /// Get a [`MerkleBlock`] inclusion proof for a [`Transaction`] with the
     /// given [`Txid`].
     pub fn get_merkle_block(&self, txid: &Txid) -> Result<Option<MerkleBlock>, Error> {
-        self.get_opt_response_hex(&format!("/tx/{}/merkleblock-proof", txid))
+        self.get_opt_response_hex(&paths::tx_merkleblock_proof(txid))
     }
 
     /// Get the spending status of an output given a [`Txid`] and the output
@@ -263,66 +532,277 @@ impl BlockingClient {
         txid: &Txid,
         index: u64,
     ) -> Result<Option<OutputStatus>, Error> {
-        self.get_opt_response_json(&format!("/tx/{}/outspend/{}", txid, index))
+        self.get_opt_response_json(&paths::tx_outspend(txid, index))
     }
 
-    /// Broadcast a [`Transaction`] to Esplora
-    pub fn broadcast(&self, transaction: &Transaction) -> Result<(), Error> {
-        let mut request = minreq::post(format!("{}/tx", self.url)).with_body(
-            serialize(transaction)
-                .to_lower_hex_string()
-                .as_bytes()
-                .to_vec(),
-        );
-
-        if let Some(proxy) = &self.proxy {
-            let proxy = Proxy::new(proxy.as_str())?;
-            request = request.with_proxy(proxy);
+    /// Get the [`Transaction`] spending an output given a [`Txid`] and the
+    /// output index, or `None` if the output is unspent.
+    ///
+    /// This takes two requests: the spending status of the output with
+    /// [`get_output_status`](Self::get_output_status), then the spending
+    /// transaction with [`get_tx`](Self::get_tx) if the output is spent.
+    pub fn get_output_spender(
+        &self,
+        txid: &Txid,
+        index: u64,
+    ) -> Result<Option<Transaction>, Error> {
+        match self.get_output_status(txid, index)? {
+            Some(OutputStatus {
+                spent: true,
+                txid: Some(spender),
+                ..
+            }) => self.get_tx(&spender),
+            _ => Ok(None),
         }
+    }
 
-        if let Some(timeout) = &self.timeout {
-            request = request.with_timeout(*timeout);
+    /// Get the spending status of the output at `outpoint`.
+    ///
+    /// See [`get_output_status`](Self::get_output_status).
+    pub fn get_outpoint_status(&self, outpoint: &OutPoint) -> Result<Option<OutputStatus>, Error> {
+        self.get_output_status(&outpoint.txid, outpoint.vout.into())
+    }
+
+    /// Get the spending status of all the outputs of a [`Transaction`] given
+    /// its [`Txid`], in output order.
+    pub fn get_output_statuses(&self, txid: &Txid) -> Result<Vec<OutputStatus>, Error> {
+        self.get_response_json(&paths::tx_outspends(txid))
+    }
+
+    /// Get transaction info given its [`Txid`] along with the spending status
+    /// of each of its outputs, combining [`get_tx_info`](Self::get_tx_info)
+    /// and [`get_output_statuses`](Self::get_output_statuses).
+    pub fn get_tx_with_spends(&self, txid: &Txid) -> Result<Option<TxWithSpends>, Error> {
+        let tx = match self.get_tx_info(txid)? {
+            Some(tx) => tx,
+            None => return Ok(None),
+        };
+        let outspends = self.get_output_statuses(txid)?;
+        Ok(Some(TxWithSpends { tx, outspends }))
+    }
+
+    /// Broadcast a [`Transaction`] to Esplora.
+    ///
+    /// Esplora has no equivalent of Bitcoin Core's `testmempoolaccept`, so
+    /// there is no way to check that a transaction would be accepted without
+    /// relaying it. A transaction rejected by the server's node, e.g. because
+    /// it conflicts with one in the mempool, returns [`Error::HttpResponse`]
+    /// with the node's reject reason as the message.
+    ///
+    /// A broadcast failing with one of the
+    /// [retryable codes](crate::Builder::retryable_codes) is retried like any
+    /// other request, except for `400 Bad Request`, the status of a rejection.
+    pub fn broadcast(&self, transaction: &Transaction) -> Result<(), Error> {
+        self.broadcast_with_headers(transaction, &[])
+    }
+
+    /// Broadcast a [`Transaction`] to Esplora, setting the given `headers` on
+    /// this request only, e.g. a request-scoped API key.
+    ///
+    /// The headers of the client are still sent, except those overridden by
+    /// `headers`, compared case-insensitively.
+    pub fn broadcast_with_headers(
+        &self,
+        transaction: &Transaction,
+        headers: &[(&str, &str)],
+    ) -> Result<(), Error> {
+        let tx_hex = serialize(transaction).to_lower_hex_string();
+        self.post_request_hex(paths::BROADCAST, tx_hex, headers)?;
+        Ok(())
+    }
+
+    /// Broadcast a transaction already serialized as hex, returning its
+    /// [`Txid`] as computed by the server.
+    ///
+    /// Unlike [`broadcast`](Self::broadcast), `tx_hex` is posted verbatim,
+    /// without being deserialized and serialized again. It is only checked to
+    /// be valid hex, otherwise [`Error::HexToBytes`] is returned.
+    pub fn broadcast_raw(&self, tx_hex: &str) -> Result<Txid, Error> {
+        Vec::<u8>::from_hex(tx_hex)?;
+        let resp = self.post_request_hex(paths::BROADCAST, tx_hex.to_string(), &[])?;
+        Txid::from_str(resp.as_str()?.trim()).map_err(Error::HexToArray)
+    }
+
+    /// Make an HTTP POST request to the given `path` with a hex string `body`,
+    /// setting `headers` on top of the client's own, along with an
+    /// idempotency key if enabled.
+    fn post_request_hex(
+        &self,
+        path: &str,
+        body: String,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error> {
+        let key;
+        let mut headers = headers.to_vec();
+        if self.idempotency
+            && !headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case(IDEMPOTENCY_KEY_HEADER))
+        {
+            key = idempotency_key();
+            headers.push((IDEMPOTENCY_KEY_HEADER, &key));
         }
 
-        match request.send() {
-            Ok(resp) if !is_status_ok(resp.status_code) => {
+        debug!("POST {}{}", self.url, path);
+        match self.post_with_retry(path, &body, &headers)? {
+            resp if !is_status_ok(resp.status_code) => {
                 let status = u16::try_from(resp.status_code).map_err(Error::StatusCode)?;
                 let message = resp.as_str().unwrap_or_default().to_string();
-                Err(Error::HttpResponse { status, message })
+                Err(http_response_error(status, message))
             }
-            Ok(_resp) => Ok(()),
-            Err(e) => Err(Error::Minreq(e)),
+            resp => Ok(resp),
         }
     }
 
     /// Get the height of the current blockchain tip.
     pub fn get_height(&self) -> Result<u32, Error> {
-        self.get_response_str("/blocks/tip/height")
+        self.get_response_str(paths::TIP_HEIGHT)
             .map(|s| u32::from_str(s.as_str()).map_err(Error::Parsing))?
     }
 
     /// Get the [`BlockHash`] of the current blockchain tip.
     pub fn get_tip_hash(&self) -> Result<BlockHash, Error> {
-        self.get_response_str("/blocks/tip/hash")
+        self.get_response_str(paths::TIP_HASH)
             .map(|s| BlockHash::from_str(s.as_str()).map_err(Error::HexToArray))?
     }
 
+    /// Get the height and [`BlockHash`] of the current blockchain tip.
+    ///
+    /// The two values are fetched with independent requests, so in rare cases
+    /// (e.g. a new block or a reorg in between) they may not refer to the same
+    /// block.
+    pub fn get_tip(&self) -> Result<(u32, BlockHash), Error> {
+        let height = self.get_height()?;
+        let hash = self.get_tip_hash()?;
+        Ok((height, hash))
+    }
+
+    /// Get the [`BlockSummary`] of the current blockchain tip.
+    ///
+    /// Some Esplora forks serve it in a single request from `/blocks/tip`,
+    /// which is tried first. Blockstream's esplora doesn't: it handles
+    /// `/blocks/tip` like `/blocks`, returning a list of the latest blocks,
+    /// while other backends may return an error. When the response isn't a
+    /// single summary, or is a `400` or `404` error, this falls back to
+    /// [`get_tip_hash`](Self::get_tip_hash) and
+    /// [`get_block_info`](Self::get_block_info), taking two more requests.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidResponse`] if the tip is no longer known by the
+    /// time its info is fetched, e.g. because of a reorg.
+    pub fn get_tip_summary(&self) -> Result<BlockSummary, Error> {
+        match self.get_opt_response_str(paths::TIP) {
+            Ok(Some(body)) => {
+                if let Ok(summary) = serde_json::from_str(&body) {
+                    return Ok(summary);
+                }
+            }
+            Ok(None) | Err(Error::HttpResponse { status: 400, .. }) => {}
+            Err(e) => return Err(e),
+        }
+        let tip_hash = self.get_tip_hash()?;
+        match self.get_block_info(&tip_hash)? {
+            Some(info) => Ok(info.into()),
+            None => Err(Error::InvalidResponse {
+                endpoint: paths::block(&tip_hash),
+                reason: "tip block not found, the chain may have been reorganized".to_string(),
+            }),
+        }
+    }
+
     /// Get the [`BlockHash`] of a specific block height
     pub fn get_block_hash(&self, block_height: u32) -> Result<BlockHash, Error> {
-        self.get_response_str(&format!("/block-height/{}", block_height))
+        self.get_response_str(&paths::block_height(block_height))
             .map(|s| BlockHash::from_str(s.as_str()).map_err(Error::HexToArray))?
     }
 
+    /// Get the [`Network`] of the server, identified by the hash of its
+    /// genesis block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownNetwork`] if the genesis block isn't the one of
+    /// a known network, such as a custom signet, see
+    /// [`get_network_with`](Self::get_network_with) for those.
+    pub fn get_network(&self) -> Result<Network, Error> {
+        self.get_network_with(&[])
+    }
+
+    /// Get the [`Network`] of the server like
+    /// [`get_network`](Self::get_network), also matching the genesis block
+    /// hashes given in `extra`, e.g. the ones of custom signets.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownNetwork`] if the genesis block is neither the
+    /// one of a known network nor in `extra`.
+    pub fn get_network_with(&self, extra: &[(BlockHash, Network)]) -> Result<Network, Error> {
+        let genesis_hash = self.get_block_hash(0)?;
+        network_from_genesis_hash(genesis_hash, extra)
+    }
+
     /// Get an map where the key is the confirmation target (in number of
     /// blocks) and the value is the estimated feerate (in sat/vB).
+    ///
+    /// If the client was built with a [fee cache TTL](Builder::fee_cache_ttl),
+    /// the estimates may be served from the cache.
     pub fn get_fee_estimates(&self) -> Result<HashMap<u16, f64>, Error> {
-        self.get_response_json("/fee-estimates")
+        let fee_cache = match &self.fee_cache {
+            Some(fee_cache) => fee_cache,
+            None => return self.get_response_json(paths::FEE_ESTIMATES),
+        };
+        if let Some(estimates) = fee_cache.fresh() {
+            return Ok(estimates);
+        }
+        match self.get_response_json(paths::FEE_ESTIMATES) {
+            Ok(estimates) => {
+                fee_cache.store(&estimates);
+                Ok(estimates)
+            }
+            Err(e) => fee_cache.stale().ok_or(e),
+        }
+    }
+
+    /// Get the fee estimates as `(target, feerate)` pairs sorted by ascending
+    /// confirmation target (in number of blocks), the feerate being in sat/vB.
+    ///
+    /// See [`get_fee_estimates`](Self::get_fee_estimates).
+    pub fn get_fee_estimates_sorted(&self) -> Result<Vec<(u16, f64)>, Error> {
+        self.get_fee_estimates().map(sort_fee_estimates)
+    }
+
+    /// Get statistics about the mempool, including its fee rate histogram.
+    pub fn get_mempool(&self) -> Result<MempoolStats, Error> {
+        self.get_response_json(paths::MEMPOOL)
+    }
+
+    /// Get a list of the last 10 transactions to enter the mempool.
+    pub fn get_mempool_recent(&self) -> Result<Vec<MempoolRecentTx>, Error> {
+        self.get_response_json(paths::MEMPOOL_RECENT)
+    }
+
+    /// Get the [`Txid`]s of all transactions currently in the mempool.
+    ///
+    /// Note that this list can be very large on busy networks.
+    pub fn get_mempool_txids(&self) -> Result<Vec<Txid>, Error> {
+        self.get_response_json(paths::MEMPOOL_TXIDS)
     }
 
     /// Get information about a specific address, includes confirmed balance and transactions in
     /// the mempool.
     pub fn get_address_stats(&self, address: &Address) -> Result<AddressStats, Error> {
-        let path = format!("/address/{address}");
+        let path = paths::address(address);
+        self.get_response_json(&path)
+    }
+
+    /// Get up to 10 addresses starting with the given `prefix`.
+    ///
+    /// The `prefix` must be a non-empty alphanumeric string no longer than an
+    /// address, otherwise [`Error::InvalidAddressPrefix`] is returned.
+    pub fn get_address_prefix(&self, prefix: &str) -> Result<Vec<String>, Error> {
+        validate_address_prefix(prefix)?;
+        let path = paths::address_prefix(prefix);
         self.get_response_json(&path)
     }
 
@@ -335,14 +815,148 @@ impl BlockingClient {
         address: &Address,
         last_seen: Option<Txid>,
     ) -> Result<Vec<Tx>, Error> {
-        let path = match last_seen {
-            Some(last_seen) => format!("/address/{address}/txs/chain/{last_seen}"),
-            None => format!("/address/{address}/txs"),
-        };
+        let path = paths::address_txs(address, last_seen);
 
         self.get_response_json(&path)
     }
 
+    /// Get a page of transaction history for the specified address, along
+    /// with the `last_seen` [`Txid`] to request the next page with, or `None`
+    /// if this is the last page.
+    ///
+    /// See [`BlockingClient::get_address_txs`].
+    pub fn get_address_txs_page(
+        &self,
+        address: &Address,
+        last_seen: Option<Txid>,
+    ) -> Result<(Vec<Tx>, Option<Txid>), Error> {
+        let txs = self.get_address_txs(address, last_seen)?;
+        let next = next_last_seen(&txs);
+        Ok((txs, next))
+    }
+
+    /// Get confirmed transaction history for the specified address, sorted
+    /// with newest first.
+    ///
+    /// Unlike [`BlockingClient::get_address_txs`], mempool transactions are
+    /// never included. Returns 25 transactions per page, more can be requested
+    /// by specifying the last txid seen by the previous query.
+    pub fn get_address_txs_chain(
+        &self,
+        address: &Address,
+        last_seen: Option<Txid>,
+    ) -> Result<Vec<Tx>, Error> {
+        let path = paths::address_txs_chain(address, last_seen);
+        self.get_response_json(&path)
+    }
+
+    /// Get the full confirmed transaction history for the specified address,
+    /// sorted with newest first.
+    ///
+    /// The history is paged through 25 transactions at a time with
+    /// [`BlockingClient::get_address_txs_chain`] until a short page is
+    /// returned, so this can take many requests for heavily used addresses.
+    /// Transactions seen twice (e.g. because of a reorg while paging) are only
+    /// returned once.
+    pub fn get_address_txs_chain_all(&self, address: &Address) -> Result<Vec<Tx>, Error> {
+        let mut txs = self.get_address_txs_chain(address, None)?;
+        let mut seen: HashSet<Txid> = txs.iter().map(|tx| tx.txid).collect();
+        let mut next = next_last_seen(&txs);
+
+        while let Some(last_seen) = next {
+            let page = self.get_address_txs_chain(address, Some(last_seen))?;
+            next = next_last_seen(&page);
+
+            let txs_len = txs.len();
+            txs.extend(page.into_iter().filter(|tx| seen.insert(tx.txid)));
+            if txs.len() == txs_len {
+                break;
+            }
+        }
+
+        Ok(txs)
+    }
+
+    /// Iterate over the full transaction history for the specified address,
+    /// sorted with newest first, including up to 50 mempool transactions.
+    ///
+    /// Unlike [`BlockingClient::get_address_txs`], the history is paged through
+    /// lazily: the next page of 25 confirmed transactions is only requested
+    /// once the transactions of the current one have all been yielded, so the
+    /// history can be processed without holding it all in memory. Transactions
+    /// seen twice (e.g. because of a reorg while paging) are only yielded once.
+    /// The iterator ends after yielding an error.
+    pub fn address_txs_iter<'a>(
+        &'a self,
+        address: &'a Address,
+    ) -> impl Iterator<Item = Result<Tx, Error>> + 'a {
+        let mut next_page = Some(None);
+        let mut seen = HashSet::new();
+        let mut page = Vec::new().into_iter();
+
+        std::iter::from_fn(move || loop {
+            if let Some(tx) = page.next() {
+                return Some(Ok(tx));
+            }
+            let last_seen = next_page.take()?;
+            match self.get_address_txs_page(address, last_seen) {
+                Ok((txs, next)) => {
+                    let txs: Vec<Tx> = txs.into_iter().filter(|tx| seen.insert(tx.txid)).collect();
+                    // Stop on a page with nothing new rather than loop forever.
+                    if !txs.is_empty() {
+                        next_page = next.map(Some);
+                    }
+                    page = txs.into_iter();
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        })
+    }
+
+    /// Get unspent transaction outputs for the specified address.
+    ///
+    /// Esplora doesn't page the UTXOs of an address, so the whole list is
+    /// loaded in memory at once, which can be large for heavily used addresses
+    /// such as exchange wallets. Backends may also refuse to list more UTXOs
+    /// than a configured limit, in which case [`Error::HttpResponse`] is
+    /// returned.
+    pub fn get_address_utxos(&self, address: &Address) -> Result<Vec<Utxo>, Error> {
+        let path = paths::address_utxos(address);
+        self.get_response_json(&path)
+    }
+
+    /// Get the unspent transaction outputs for the specified address, keeping
+    /// only the confirmed or unconfirmed ones according to `filter`.
+    pub fn get_address_utxos_filtered(
+        &self,
+        address: &Address,
+        filter: UtxoFilter,
+    ) -> Result<Vec<Utxo>, Error> {
+        let mut utxos = self.get_address_utxos(address)?;
+        utxos.retain(|utxo| filter.matches(utxo));
+        Ok(utxos)
+    }
+
+    /// Get unconfirmed transaction history for the specified address, sorted
+    /// with newest first. Returns up to 50 transactions (no paging).
+    pub fn get_address_txs_mempool(&self, address: &Address) -> Result<Vec<Tx>, Error> {
+        let path = paths::address_txs_mempool(address);
+        self.get_response_json(&path)
+    }
+
+    /// Get statistics about a particular [`Script`] hash's confirmed and mempool
+    /// transactions.
+    pub fn get_scripthash_stats(&self, script: &Script) -> Result<ScriptHashStats, Error> {
+        let path = paths::scripthash(script);
+        self.get_response_json(&path)
+    }
+
+    /// Get unspent transaction outputs for the specified [`Script`] hash.
+    pub fn get_scripthash_utxos(&self, script: &Script) -> Result<Vec<Utxo>, Error> {
+        let path = paths::scripthash_utxos(script);
+        self.get_response_json(&path)
+    }
+
     /// Get confirmed transaction history for the specified address/scripthash,
     /// sorted with newest first. Returns 25 transactions per page.
     /// More can be requested by specifying the last txid seen by the previous
@@ -352,41 +966,265 @@ impl BlockingClient {
         script: &Script,
         last_seen: Option<Txid>,
     ) -> Result<Vec<Tx>, Error> {
-        let script_hash = sha256::Hash::hash(script.as_bytes());
-        let path = match last_seen {
-            Some(last_seen) => format!("/scripthash/{:x}/txs/chain/{}", script_hash, last_seen),
-            None => format!("/scripthash/{:x}/txs", script_hash),
-        };
+        let path = paths::scripthash_txs(script, last_seen);
+        self.get_response_json(&path)
+    }
+
+    /// Get confirmed transaction history for the given script hash, the
+    /// SHA256 hash of a script's bytes under which Esplora indexes it.
+    ///
+    /// Same as [`scripthash_txs`](Self::scripthash_txs), for callers that only
+    /// have the hash of the script rather than the script itself.
+    pub fn scripthash_txs_by_hash(
+        &self,
+        script_hash: &sha256::Hash,
+        last_seen: Option<Txid>,
+    ) -> Result<Vec<Tx>, Error> {
+        let path = paths::scripthash_txs_by_hash(script_hash, last_seen);
         self.get_response_json(&path)
     }
 
+    /// Get a page of transaction history for the specified [`Script`] hash,
+    /// along with the `last_seen` [`Txid`] to request the next page with, or
+    /// `None` if this is the last page.
+    ///
+    /// See [`BlockingClient::scripthash_txs`].
+    pub fn scripthash_txs_page(
+        &self,
+        script: &Script,
+        last_seen: Option<Txid>,
+    ) -> Result<(Vec<Tx>, Option<Txid>), Error> {
+        let txs = self.scripthash_txs(script, last_seen)?;
+        let next = next_last_seen(&txs);
+        Ok((txs, next))
+    }
+
+    /// Get unconfirmed transaction history for the specified [`Script`] hash,
+    /// sorted with newest first. Returns up to 50 transactions (no paging).
+    pub fn scripthash_txs_mempool(&self, script: &Script) -> Result<Vec<Tx>, Error> {
+        let path = paths::scripthash_txs_mempool(script);
+        self.get_response_json(&path)
+    }
+
+    /// Get the progress of the current difficulty adjustment period, along
+    /// with an estimate of the next difficulty change.
+    ///
+    /// Requires a mempool.space backend, Blockstream's esplora doesn't serve
+    /// this endpoint.
+    #[cfg(feature = "mempool-space")]
+    pub fn get_difficulty_adjustment(&self) -> Result<DifficultyAdjustment, Error> {
+        self.get_response_json(paths::DIFFICULTY_ADJUSTMENT)
+    }
+
+    /// Get the feerates recommended by the server, in sat/vB, to confirm in
+    /// the next block, within half an hour, within an hour or eventually.
+    ///
+    /// Requires a mempool.space backend, Blockstream's esplora doesn't serve
+    /// this endpoint. See [`get_fee_estimates`](Self::get_fee_estimates) for
+    /// the estimates served by both.
+    #[cfg(feature = "mempool-space")]
+    pub fn get_recommended_fees(&self) -> Result<RecommendedFees, Error> {
+        self.get_response_json(paths::RECOMMENDED_FEES)
+    }
+
+    /// Get information about a Liquid asset given its `asset_id` in hex, or
+    /// `None` if the asset is unknown.
+    ///
+    /// Requires a Liquid-aware Esplora backend. Returns
+    /// [`Error::InvalidAssetId`] if `asset_id` isn't 64 hex characters.
+    #[cfg(feature = "elements")]
+    pub fn get_asset(&self, asset_id: &str) -> Result<Option<AssetInfo>, Error> {
+        validate_asset_id(asset_id)?;
+        let path = paths::asset(asset_id);
+        self.get_opt_response_json(&path)
+    }
+
+    /// Get transaction history for the specified Liquid asset, sorted with
+    /// newest first.
+    ///
+    /// Returns up to 50 mempool transactions plus the first 25 confirmed
+    /// transactions. More can be requested by specifying the last txid seen
+    /// by the previous query. Requires a Liquid-aware Esplora backend.
+    #[cfg(feature = "elements")]
+    pub fn get_asset_txs(
+        &self,
+        asset_id: &str,
+        last_seen: Option<Txid>,
+    ) -> Result<Vec<AssetTx>, Error> {
+        validate_asset_id(asset_id)?;
+        let path = paths::asset_txs(asset_id, last_seen);
+        self.get_response_json(&path)
+    }
+
+    /// Get the circulating supply of a Liquid asset, in its base units.
+    ///
+    /// Requires a Liquid-aware Esplora backend, which fails the request if the
+    /// asset has blinded issuances.
+    #[cfg(feature = "elements")]
+    pub fn get_asset_supply(&self, asset_id: &str) -> Result<u64, Error> {
+        validate_asset_id(asset_id)?;
+        let path = paths::asset_supply(asset_id);
+        self.get_response_str(&path)
+            .map(|supply| u64::from_str(supply.trim()).map_err(Error::Parsing))?
+    }
+
     /// Gets some recent block summaries starting at the tip or at `height` if
     /// provided.
     ///
     /// The maximum number of summaries returned depends on the backend itself:
     /// esplora returns `10` while [mempool.space](https://mempool.space/docs/api) returns `15`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidResponse`] if the server returns no summaries,
+    /// since at least the block at `height` (or the tip) is always expected.
     pub fn get_blocks(&self, height: Option<u32>) -> Result<Vec<BlockSummary>, Error> {
-        let path = match height {
-            Some(height) => format!("/blocks/{}", height),
-            None => "/blocks".to_string(),
-        };
+        let path = paths::blocks(height);
         let blocks: Vec<BlockSummary> = self.get_response_json(&path)?;
         if blocks.is_empty() {
-            return Err(Error::InvalidResponse);
+            return Err(Error::InvalidResponse {
+                endpoint: path,
+                reason: "empty blocks array".to_string(),
+            });
+        }
+        Ok(blocks)
+    }
+
+    /// Gets some recent blocks starting at the tip or at `height` if provided,
+    /// with the details of [`BlockInfo`] such as their number of transactions,
+    /// size and weight.
+    ///
+    /// This is the same request as [`BlockingClient::get_blocks`], the backends
+    /// returning these details in `/blocks` too, so it costs a single request
+    /// rather than one [`BlockingClient::get_block_info`] per block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidResponse`] if the server returns no blocks,
+    /// since at least the block at `height` (or the tip) is always expected.
+    pub fn get_blocks_detailed(&self, height: Option<u32>) -> Result<Vec<BlockInfo>, Error> {
+        let path = paths::blocks(height);
+        let blocks: Vec<BlockInfo> = self.get_response_json(&path)?;
+        if blocks.is_empty() {
+            return Err(Error::InvalidResponse {
+                endpoint: path,
+                reason: "empty blocks array".to_string(),
+            });
         }
         Ok(blocks)
     }
 
-    /// Sends a GET request to the given `url`, retrying failed attempts
-    /// for retryable error codes until max retries hit.
-    fn get_with_retry(&self, url: &str) -> Result<Response, Error> {
+    /// Sends a GET request to the given `url` with the given `headers` on top
+    /// of the client's own, retrying failed attempts for retryable error
+    /// codes until max retries hit or the total timeout is exceeded.
+    fn get_with_retry_and_headers(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error> {
+        self.send_with_retry(Method::Get, url, None, headers, &self.retryable_codes)
+    }
+
+    /// Sends a POST request with the given `body` to the given `url`,
+    /// retrying failed attempts like
+    /// [`get_with_retry_and_headers`](Self::get_with_retry_and_headers).
+    ///
+    /// A `400 Bad Request` is never retried, even if configured as retryable:
+    /// it is how Esplora reports that its node rejected a transaction, which
+    /// won't succeed on a later attempt.
+    fn post_with_retry(
+        &self,
+        url: &str,
+        body: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<Response, Error> {
+        let retryable_codes: Vec<u16> = self
+            .retryable_codes
+            .iter()
+            .copied()
+            .filter(|&code| code != 400)
+            .collect();
+        self.send_with_retry(Method::Post, url, Some(body), headers, &retryable_codes)
+    }
+
+    /// Sends a request with the given `method` and `body` to the given `url`,
+    /// retrying failed attempts for the given `retryable_codes` until max
+    /// retries hit or the total timeout is exceeded.
+    fn send_with_retry(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&str>,
+        headers: &[(&str, &str)],
+        retryable_codes: &[u16],
+    ) -> Result<Response, Error> {
         let mut delay = BASE_BACKOFF_MILLIS;
         let mut attempts = 0;
+        let deadline = self
+            .total_timeout
+            .and_then(|timeout| Instant::now().checked_add(timeout));
 
         loop {
-            match self.get_request(url)?.send()? {
-                resp if attempts < self.max_retries && is_status_retryable(resp.status_code) => {
-                    thread::sleep(delay);
+            trace!("{} {}{}", method, self.url, url);
+            #[cfg(feature = "tracing")]
+            let span = crate::request_span(url, attempts);
+            let start = Instant::now();
+            let resp = {
+                #[cfg(feature = "tracing")]
+                let _entered = span.enter();
+                let mut request = Request::new(method.clone(), format!("{}{}", self.url, url));
+                if let Some(body) = body {
+                    request = request.with_body(body);
+                }
+                self.prepare_request(request, headers)?.send()
+            };
+            let elapsed = start.elapsed();
+            let status = resp
+                .as_ref()
+                .ok()
+                .and_then(|resp| u16::try_from(resp.status_code).ok());
+            #[cfg(feature = "tracing")]
+            crate::record_response(&span, status, elapsed);
+            if let Some(on_request) = &self.on_request {
+                on_request.observe(url, elapsed, status);
+            }
+
+            match resp? {
+                resp if attempts < self.max_retries()
+                    && is_status_retryable(resp.status_code, retryable_codes) =>
+                {
+                    let retry_after = resp
+                        .headers
+                        .get("retry-after")
+                        .and_then(|value| retry_after_delay(value));
+                    let backoff = if self.retry_jitter {
+                        jitter(delay)
+                    } else {
+                        delay
+                    };
+                    let wait = retry_after.unwrap_or(backoff);
+                    let past_deadline = deadline.map_or(false, |deadline| {
+                        Instant::now()
+                            .checked_add(wait)
+                            .map_or(true, |retry_at| retry_at >= deadline)
+                    });
+                    if past_deadline {
+                        debug!(
+                            "HTTP {} response from {}{}, not retrying past the total timeout",
+                            resp.status_code, self.url, url
+                        );
+                        return Err(Error::Timeout);
+                    }
+                    debug!(
+                        "HTTP {} response from {}{}, retrying in {:?} (retry {} of {})",
+                        resp.status_code,
+                        self.url,
+                        url,
+                        wait,
+                        attempts + 1,
+                        self.max_retries()
+                    );
+                    thread::sleep(wait);
                     attempts += 1;
                     delay *= 2;
                 }
@@ -396,15 +1234,159 @@ impl BlockingClient {
     }
 }
 
-fn is_status_ok(status: i32) -> bool {
-    status == 200
+/// The common read methods of a blocking Esplora client, along with
+/// [`broadcast`](BlockingEsploraApi::broadcast), to write code that is generic
+/// over the client, e.g. to substitute a mock in tests.
+///
+/// See [`BlockingClient`] for the documentation of each method. The async
+/// client implements the equivalent [`EsploraApi`](crate::EsploraApi) instead.
+pub trait BlockingEsploraApi {
+    /// Get a [`Transaction`] option given its [`Txid`].
+    fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, Error>;
+
+    /// Get transaction info given its [`Txid`].
+    fn get_tx_info(&self, txid: &Txid) -> Result<Option<Tx>, Error>;
+
+    /// Get the status of a [`Transaction`] given its [`Txid`].
+    fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, Error>;
+
+    /// Get the spending status of an output given a [`Txid`] and the output
+    /// index.
+    fn get_output_status(&self, txid: &Txid, index: u64) -> Result<Option<OutputStatus>, Error>;
+
+    /// Get the height of the current blockchain tip.
+    fn get_height(&self) -> Result<u32, Error>;
+
+    /// Get the [`BlockHash`] of the current blockchain tip.
+    fn get_tip_hash(&self) -> Result<BlockHash, Error>;
+
+    /// Get the [`BlockHash`] of a specific block height.
+    fn get_block_hash(&self, block_height: u32) -> Result<BlockHash, Error>;
+
+    /// Get a [`BlockHeader`] given a particular block hash.
+    fn get_header_by_hash(&self, block_hash: &BlockHash) -> Result<BlockHeader, Error>;
+
+    /// Get the [`BlockStatus`] given a particular [`BlockHash`].
+    fn get_block_status(&self, block_hash: &BlockHash) -> Result<BlockStatus, Error>;
+
+    /// Get confirmed transaction history for the specified address/scripthash,
+    /// sorted with newest first.
+    fn scripthash_txs(&self, script: &Script, last_seen: Option<Txid>) -> Result<Vec<Tx>, Error>;
+
+    /// Get a map where the key is the confirmation target (in number of
+    /// blocks) and the value is the estimated feerate (in sat/vB).
+    fn get_fee_estimates(&self) -> Result<HashMap<u16, f64>, Error>;
+
+    /// Broadcast a [`Transaction`] to Esplora.
+    fn broadcast(&self, transaction: &Transaction) -> Result<(), Error>;
+}
+
+impl BlockingEsploraApi for BlockingClient {
+    fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, Error> {
+        BlockingClient::get_tx(self, txid)
+    }
+
+    fn get_tx_info(&self, txid: &Txid) -> Result<Option<Tx>, Error> {
+        BlockingClient::get_tx_info(self, txid)
+    }
+
+    fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, Error> {
+        BlockingClient::get_tx_status(self, txid)
+    }
+
+    fn get_output_status(&self, txid: &Txid, index: u64) -> Result<Option<OutputStatus>, Error> {
+        BlockingClient::get_output_status(self, txid, index)
+    }
+
+    fn get_height(&self) -> Result<u32, Error> {
+        BlockingClient::get_height(self)
+    }
+
+    fn get_tip_hash(&self) -> Result<BlockHash, Error> {
+        BlockingClient::get_tip_hash(self)
+    }
+
+    fn get_block_hash(&self, block_height: u32) -> Result<BlockHash, Error> {
+        BlockingClient::get_block_hash(self, block_height)
+    }
+
+    fn get_header_by_hash(&self, block_hash: &BlockHash) -> Result<BlockHeader, Error> {
+        BlockingClient::get_header_by_hash(self, block_hash)
+    }
+
+    fn get_block_status(&self, block_hash: &BlockHash) -> Result<BlockStatus, Error> {
+        BlockingClient::get_block_status(self, block_hash)
+    }
+
+    fn scripthash_txs(&self, script: &Script, last_seen: Option<Txid>) -> Result<Vec<Tx>, Error> {
+        BlockingClient::scripthash_txs(self, script, last_seen)
+    }
+
+    fn get_fee_estimates(&self) -> Result<HashMap<u16, f64>, Error> {
+        BlockingClient::get_fee_estimates(self)
+    }
+
+    fn broadcast(&self, transaction: &Transaction) -> Result<(), Error> {
+        BlockingClient::broadcast(self, transaction)
+    }
+}
+
+/// The proxy and headers of a [`BlockingClient`], parsed and validated once
+/// rather than for every request.
+#[derive(Debug, Clone)]
+struct RequestOptions {
+    /// The `proxy`, `headers` and `user_agent` fields these were prepared
+    /// from.
+    source: (Option<String>, HashMap<String, String>, Option<String>),
+    /// The parsed proxy, if any.
+    proxy: Option<Proxy>,
+    /// The headers to set on every request, `User-Agent` included.
+    headers: Vec<(String, String)>,
 }
 
-fn is_status_not_found(status: i32) -> bool {
-    status == 404
+impl RequestOptions {
+    /// Parse the given `proxy` and validate the given `headers`, adding a
+    /// `User-Agent` header unless they already have one.
+    fn new(
+        proxy: Option<&String>,
+        headers: &HashMap<String, String>,
+        user_agent: Option<&String>,
+    ) -> Result<Self, Error> {
+        let parsed_proxy = proxy.map(Proxy::new).transpose()?;
+        for (key, value) in headers {
+            parse_header(key, value)?;
+        }
+
+        let mut request_headers: Vec<(String, String)> = headers
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        if !headers
+            .keys()
+            .any(|key| key.eq_ignore_ascii_case("user-agent"))
+        {
+            let user_agent = user_agent.map_or(DEFAULT_USER_AGENT, String::as_str);
+            request_headers.push(("User-Agent".to_string(), user_agent.to_string()));
+        }
+
+        Ok(RequestOptions {
+            source: (proxy.cloned(), headers.clone(), user_agent.cloned()),
+            proxy: parsed_proxy,
+            headers: request_headers,
+        })
+    }
+
+    /// Whether these were prepared from the current fields of `client`.
+    fn is_prepared_from(&self, client: &BlockingClient) -> bool {
+        let (proxy, headers, user_agent) = &self.source;
+        *proxy == client.proxy && *headers == client.headers && *user_agent == client.user_agent
+    }
+}
+
+fn is_status_ok(status: i32) -> bool {
+    status == 200
 }
 
-fn is_status_retryable(status: i32) -> bool {
-    let status = status as u16;
-    RETRYABLE_ERROR_CODES.contains(&status)
+fn is_status_retryable(status: i32, retryable_codes: &[u16]) -> bool {
+    u16::try_from(status).map_or(false, |status| retryable_codes.contains(&status))
 }
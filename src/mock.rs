@@ -0,0 +1,391 @@
+// Bitcoin Dev Kit
+//
+// Copyright (c) 2020-2021 Bitcoin Dev Kit Developers
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! In-memory mock of an Esplora server, to test code built on this crate
+//! without running one.
+//!
+//! [`MockClient`] serves canned blocks, transactions and fee estimates
+//! through the [`BlockingEsploraApi`](crate::BlockingEsploraApi) and
+//! [`EsploraApi`](crate::EsploraApi) traits, e.g. to simulate a reorg:
+//!
+//! ```
+//! # #[cfg(feature = "blocking")]
+//! # {
+//! use bitcoin::block::{Header, Version};
+//! use bitcoin::hashes::Hash;
+//! use bitcoin::{BlockHash, CompactTarget, TxMerkleNode};
+//! use esplora_client::mock::MockClient;
+//! use esplora_client::BlockingEsploraApi;
+//!
+//! fn header(prev_blockhash: BlockHash, nonce: u32) -> Header {
+//!     Header {
+//!         version: Version::TWO,
+//!         prev_blockhash,
+//!         merkle_root: TxMerkleNode::all_zeros(),
+//!         time: 0,
+//!         bits: CompactTarget::from_consensus(0x207fffff),
+//!         nonce,
+//!     }
+//! }
+//!
+//! let genesis = header(BlockHash::all_zeros(), 0);
+//! let stale = header(genesis.block_hash(), 1);
+//! let client = MockClient::new();
+//! client.set_chain(vec![genesis, stale]);
+//! assert_eq!(client.get_tip_hash().unwrap(), stale.block_hash());
+//!
+//! let reorged = header(genesis.block_hash(), 2);
+//! client.set_chain(vec![genesis, reorged, header(reorged.block_hash(), 3)]);
+//! assert_eq!(client.get_height().unwrap(), 2);
+//! assert!(
+//!     !client
+//!         .get_block_status(&stale.block_hash())
+//!         .unwrap()
+//!         .in_best_chain
+//! );
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use bitcoin::block::Header as BlockHeader;
+use bitcoin::{BlockHash, Script, Transaction, Txid};
+
+#[cfg(feature = "async")]
+use crate::r#async::ApiFuture;
+use crate::{BlockStatus, Error, OutputStatus, Tx, TxStatus, CHAIN_TXS_PAGE_SIZE};
+
+/// A mock Esplora client serving canned responses from memory.
+///
+/// Clones share the same responses, so a test can keep a clone to update
+/// them while the code under test uses another. Unknown blocks and
+/// transactions are reported the way Esplora does, mostly as
+/// [`Error::HttpResponse`] with a `404` status.
+#[derive(Debug, Clone, Default)]
+pub struct MockClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    /// Headers of all the blocks ever added, including stale ones.
+    headers: HashMap<BlockHash, BlockHeader>,
+    /// Hashes of the blocks of the best chain, by height.
+    best_chain: Vec<BlockHash>,
+    txs: HashMap<Txid, Tx>,
+    output_statuses: HashMap<(Txid, u64), OutputStatus>,
+    fee_estimates: HashMap<u16, f64>,
+    broadcasted: Vec<Transaction>,
+    /// Errors to return from the next calls, in order.
+    errors: VecDeque<Error>,
+}
+
+impl MockClient {
+    /// Create a mock client without any block or transaction.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the best chain with `headers`, starting from height 0.
+    ///
+    /// The blocks of the previous best chain which aren't part of the new
+    /// one are kept as stale blocks, as after a reorg.
+    pub fn set_chain(&self, headers: Vec<BlockHeader>) {
+        let mut state = self.state();
+        state.best_chain = headers.iter().map(|header| header.block_hash()).collect();
+        state.headers.extend(
+            headers
+                .into_iter()
+                .map(|header| (header.block_hash(), header)),
+        );
+    }
+
+    /// Add a transaction, or replace the one with the same `txid`.
+    ///
+    /// Its `status` is served as is, it is up to the test to keep it
+    /// consistent with the best chain.
+    pub fn insert_tx(&self, tx: Tx) {
+        self.state().txs.insert(tx.txid, tx);
+    }
+
+    /// Remove a transaction, as if it was evicted from the mempool.
+    pub fn remove_tx(&self, txid: &Txid) -> Option<Tx> {
+        self.state().txs.remove(txid)
+    }
+
+    /// Set the spending status of the output `index` of `txid`.
+    pub fn insert_output_status(&self, txid: Txid, index: u64, status: OutputStatus) {
+        self.state().output_statuses.insert((txid, index), status);
+    }
+
+    /// Set the fee estimates.
+    pub fn set_fee_estimates(&self, estimates: HashMap<u16, f64>) {
+        self.state().fee_estimates = estimates;
+    }
+
+    /// Make the next call fail with `error`.
+    ///
+    /// Errors pushed by successive calls are returned by successive calls to
+    /// the client, whatever their method.
+    pub fn push_error(&self, error: Error) {
+        self.state().errors.push_back(error);
+    }
+
+    /// Get the transactions broadcast through the client, in order.
+    pub fn broadcasted(&self) -> Vec<Transaction> {
+        self.state().broadcasted.clone()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Lock the state to serve a call, unless an error was pushed for it.
+    fn serve(&self) -> Result<MutexGuard<'_, MockState>, Error> {
+        let mut state = self.state();
+        match state.errors.pop_front() {
+            Some(error) => Err(error),
+            None => Ok(state),
+        }
+    }
+
+    fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, Error> {
+        Ok(self.serve()?.txs.get(txid).map(Tx::to_tx))
+    }
+
+    fn get_tx_info(&self, txid: &Txid) -> Result<Option<Tx>, Error> {
+        Ok(self.serve()?.txs.get(txid).cloned())
+    }
+
+    fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, Error> {
+        match self.serve()?.txs.get(txid) {
+            Some(tx) => Ok(tx.status.clone()),
+            None => Err(not_found("Transaction not found")),
+        }
+    }
+
+    fn get_output_status(&self, txid: &Txid, index: u64) -> Result<Option<OutputStatus>, Error> {
+        Ok(self.serve()?.output_statuses.get(&(*txid, index)).cloned())
+    }
+
+    fn get_height(&self) -> Result<u32, Error> {
+        match self.serve()?.best_chain.len() {
+            0 => Err(not_found("Block not found")),
+            len => Ok(len as u32 - 1),
+        }
+    }
+
+    fn get_tip_hash(&self) -> Result<BlockHash, Error> {
+        match self.serve()?.best_chain.last() {
+            Some(block_hash) => Ok(*block_hash),
+            None => Err(not_found("Block not found")),
+        }
+    }
+
+    fn get_block_hash(&self, block_height: u32) -> Result<BlockHash, Error> {
+        match self.serve()?.best_chain.get(block_height as usize) {
+            Some(block_hash) => Ok(*block_hash),
+            None => Err(not_found("Block not found")),
+        }
+    }
+
+    fn get_header_by_hash(&self, block_hash: &BlockHash) -> Result<BlockHeader, Error> {
+        match self.serve()?.headers.get(block_hash) {
+            Some(header) => Ok(*header),
+            None => Err(not_found("Block not found")),
+        }
+    }
+
+    fn get_block_status(&self, block_hash: &BlockHash) -> Result<BlockStatus, Error> {
+        let state = self.serve()?;
+        let height = state.best_chain.iter().position(|hash| hash == block_hash);
+        Ok(BlockStatus {
+            in_best_chain: height.is_some(),
+            height: height.map(|height| height as u32),
+            next_best: height.and_then(|height| state.best_chain.get(height + 1).copied()),
+        })
+    }
+
+    fn scripthash_txs(&self, script: &Script, last_seen: Option<Txid>) -> Result<Vec<Tx>, Error> {
+        let state = self.serve()?;
+        let mut txs: Vec<&Tx> = state
+            .txs
+            .values()
+            .filter(|tx| {
+                tx.vout
+                    .iter()
+                    .any(|vout| vout.scriptpubkey.as_script() == script)
+                    || tx.vin.iter().any(|vin| {
+                        vin.prevout
+                            .as_ref()
+                            .map_or(false, |prevout| prevout.scriptpubkey.as_script() == script)
+                    })
+            })
+            .collect();
+        // Mempool transactions first, then confirmed ones with the newest first.
+        txs.sort_by_key(|tx| {
+            (
+                tx.status.confirmed,
+                std::cmp::Reverse(tx.status.block_height),
+                tx.txid,
+            )
+        });
+
+        let (mempool, confirmed): (Vec<&Tx>, Vec<&Tx>) =
+            txs.into_iter().partition(|tx| !tx.status.confirmed);
+        let txs = match last_seen {
+            Some(last_seen) => confirmed
+                .into_iter()
+                .skip_while(|tx| tx.txid != last_seen)
+                .skip(1)
+                .take(CHAIN_TXS_PAGE_SIZE)
+                .collect::<Vec<_>>(),
+            None => mempool
+                .into_iter()
+                .chain(confirmed.into_iter().take(CHAIN_TXS_PAGE_SIZE))
+                .collect(),
+        };
+        Ok(txs.into_iter().cloned().collect())
+    }
+
+    fn get_fee_estimates(&self) -> Result<HashMap<u16, f64>, Error> {
+        Ok(self.serve()?.fee_estimates.clone())
+    }
+
+    fn broadcast(&self, transaction: &Transaction) -> Result<(), Error> {
+        self.serve()?.broadcasted.push(transaction.clone());
+        Ok(())
+    }
+}
+
+/// The error of a `404` response with the given message.
+fn not_found(message: &str) -> Error {
+    Error::HttpResponse {
+        status: 404,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl crate::BlockingEsploraApi for MockClient {
+    fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, Error> {
+        MockClient::get_tx(self, txid)
+    }
+
+    fn get_tx_info(&self, txid: &Txid) -> Result<Option<Tx>, Error> {
+        MockClient::get_tx_info(self, txid)
+    }
+
+    fn get_tx_status(&self, txid: &Txid) -> Result<TxStatus, Error> {
+        MockClient::get_tx_status(self, txid)
+    }
+
+    fn get_output_status(&self, txid: &Txid, index: u64) -> Result<Option<OutputStatus>, Error> {
+        MockClient::get_output_status(self, txid, index)
+    }
+
+    fn get_height(&self) -> Result<u32, Error> {
+        MockClient::get_height(self)
+    }
+
+    fn get_tip_hash(&self) -> Result<BlockHash, Error> {
+        MockClient::get_tip_hash(self)
+    }
+
+    fn get_block_hash(&self, block_height: u32) -> Result<BlockHash, Error> {
+        MockClient::get_block_hash(self, block_height)
+    }
+
+    fn get_header_by_hash(&self, block_hash: &BlockHash) -> Result<BlockHeader, Error> {
+        MockClient::get_header_by_hash(self, block_hash)
+    }
+
+    fn get_block_status(&self, block_hash: &BlockHash) -> Result<BlockStatus, Error> {
+        MockClient::get_block_status(self, block_hash)
+    }
+
+    fn scripthash_txs(&self, script: &Script, last_seen: Option<Txid>) -> Result<Vec<Tx>, Error> {
+        MockClient::scripthash_txs(self, script, last_seen)
+    }
+
+    fn get_fee_estimates(&self) -> Result<HashMap<u16, f64>, Error> {
+        MockClient::get_fee_estimates(self)
+    }
+
+    fn broadcast(&self, transaction: &Transaction) -> Result<(), Error> {
+        MockClient::broadcast(self, transaction)
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::EsploraApi for MockClient {
+    fn get_tx<'a>(&'a self, txid: &'a Txid) -> ApiFuture<'a, Option<Transaction>> {
+        ready(MockClient::get_tx(self, txid))
+    }
+
+    fn get_tx_info<'a>(&'a self, txid: &'a Txid) -> ApiFuture<'a, Option<Tx>> {
+        ready(MockClient::get_tx_info(self, txid))
+    }
+
+    fn get_tx_status<'a>(&'a self, txid: &'a Txid) -> ApiFuture<'a, TxStatus> {
+        ready(MockClient::get_tx_status(self, txid))
+    }
+
+    fn get_output_status<'a>(
+        &'a self,
+        txid: &'a Txid,
+        index: u64,
+    ) -> ApiFuture<'a, Option<OutputStatus>> {
+        ready(MockClient::get_output_status(self, txid, index))
+    }
+
+    fn get_height(&self) -> ApiFuture<'_, u32> {
+        ready(MockClient::get_height(self))
+    }
+
+    fn get_tip_hash(&self) -> ApiFuture<'_, BlockHash> {
+        ready(MockClient::get_tip_hash(self))
+    }
+
+    fn get_block_hash(&self, block_height: u32) -> ApiFuture<'_, BlockHash> {
+        ready(MockClient::get_block_hash(self, block_height))
+    }
+
+    fn get_header_by_hash<'a>(&'a self, block_hash: &'a BlockHash) -> ApiFuture<'a, BlockHeader> {
+        ready(MockClient::get_header_by_hash(self, block_hash))
+    }
+
+    fn get_block_status<'a>(&'a self, block_hash: &'a BlockHash) -> ApiFuture<'a, BlockStatus> {
+        ready(MockClient::get_block_status(self, block_hash))
+    }
+
+    fn scripthash_txs<'a>(
+        &'a self,
+        script: &'a Script,
+        last_seen: Option<Txid>,
+    ) -> ApiFuture<'a, Vec<Tx>> {
+        ready(MockClient::scripthash_txs(self, script, last_seen))
+    }
+
+    fn get_fee_estimates(&self) -> ApiFuture<'_, HashMap<u16, f64>> {
+        ready(MockClient::get_fee_estimates(self))
+    }
+
+    fn broadcast<'a>(&'a self, transaction: &'a Transaction) -> ApiFuture<'a, ()> {
+        ready(MockClient::broadcast(self, transaction))
+    }
+}
+
+/// Wrap an already computed `result` in an [`ApiFuture`].
+#[cfg(feature = "async")]
+fn ready<'a, T: Send + 'a>(result: Result<T, Error>) -> ApiFuture<'a, T> {
+    Box::pin(std::future::ready(result))
+}
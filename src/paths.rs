@@ -0,0 +1,184 @@
+//! Paths of the Esplora API endpoints
+//!
+//! Shared by the blocking and async clients so they can't drift apart. Every
+//! path is relative to the client's base URL.
+//!
+//! See: <https://github.com/Blockstream/esplora/blob/master/API.md>
+
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::{Address, BlockHash, Script, Txid};
+
+/// Path to broadcast a transaction with a `POST` request.
+pub const BROADCAST: &str = "/tx";
+/// Path of the height of the current blockchain tip.
+pub const TIP_HEIGHT: &str = "/blocks/tip/height";
+/// Path of the hash of the current blockchain tip.
+pub const TIP_HASH: &str = "/blocks/tip/hash";
+/// Path of the summary of the current blockchain tip, only served by some
+/// backends.
+pub const TIP: &str = "/blocks/tip";
+/// Path of the fee estimates.
+pub const FEE_ESTIMATES: &str = "/fee-estimates";
+/// Path of the mempool statistics.
+pub const MEMPOOL: &str = "/mempool";
+/// Path of the transactions that most recently entered the mempool.
+pub const MEMPOOL_RECENT: &str = "/mempool/recent";
+/// Path of the txids of all the transactions in the mempool.
+pub const MEMPOOL_TXIDS: &str = "/mempool/txids";
+
+pub fn tx(txid: &Txid) -> String {
+    format!("/tx/{txid}")
+}
+
+pub fn tx_raw(txid: &Txid) -> String {
+    format!("/tx/{txid}/raw")
+}
+
+pub fn tx_hex(txid: &Txid) -> String {
+    format!("/tx/{txid}/hex")
+}
+
+pub fn tx_status(txid: &Txid) -> String {
+    format!("/tx/{txid}/status")
+}
+
+pub fn tx_merkle_proof(txid: &Txid) -> String {
+    format!("/tx/{txid}/merkle-proof")
+}
+
+pub fn tx_merkleblock_proof(txid: &Txid) -> String {
+    format!("/tx/{txid}/merkleblock-proof")
+}
+
+pub fn tx_outspend(txid: &Txid, index: u64) -> String {
+    format!("/tx/{txid}/outspend/{index}")
+}
+
+pub fn tx_outspends(txid: &Txid) -> String {
+    format!("/tx/{txid}/outspends")
+}
+
+pub fn block(block_hash: &BlockHash) -> String {
+    format!("/block/{block_hash}")
+}
+
+pub fn block_header(block_hash: &BlockHash) -> String {
+    format!("/block/{block_hash}/header")
+}
+
+pub fn block_status(block_hash: &BlockHash) -> String {
+    format!("/block/{block_hash}/status")
+}
+
+pub fn block_raw(block_hash: &BlockHash) -> String {
+    format!("/block/{block_hash}/raw")
+}
+
+pub fn block_txid(block_hash: &BlockHash, index: usize) -> String {
+    format!("/block/{block_hash}/txid/{index}")
+}
+
+pub fn block_txids(block_hash: &BlockHash) -> String {
+    format!("/block/{block_hash}/txids")
+}
+
+pub fn block_txs(block_hash: &BlockHash, start_index: Option<usize>) -> String {
+    match start_index {
+        Some(start_index) => format!("/block/{block_hash}/txs/{start_index}"),
+        None => format!("/block/{block_hash}/txs"),
+    }
+}
+
+pub fn block_height(block_height: u32) -> String {
+    format!("/block-height/{block_height}")
+}
+
+pub fn blocks(height: Option<u32>) -> String {
+    match height {
+        Some(height) => format!("/blocks/{height}"),
+        None => "/blocks".to_string(),
+    }
+}
+
+pub fn address(address: &Address) -> String {
+    format!("/address/{address}")
+}
+
+pub fn address_prefix(prefix: &str) -> String {
+    format!("/address-prefix/{prefix}")
+}
+
+pub fn address_txs(address: &Address, last_seen: Option<Txid>) -> String {
+    match last_seen {
+        Some(last_seen) => format!("/address/{address}/txs/chain/{last_seen}"),
+        None => format!("/address/{address}/txs"),
+    }
+}
+
+pub fn address_txs_chain(address: &Address, last_seen: Option<Txid>) -> String {
+    match last_seen {
+        Some(last_seen) => format!("/address/{address}/txs/chain/{last_seen}"),
+        None => format!("/address/{address}/txs/chain"),
+    }
+}
+
+pub fn address_txs_mempool(address: &Address) -> String {
+    format!("/address/{address}/txs/mempool")
+}
+
+pub fn address_utxos(address: &Address) -> String {
+    format!("/address/{address}/utxo")
+}
+
+/// Esplora indexes scripts by the SHA256 hash of their bytes.
+fn script_hash(script: &Script) -> sha256::Hash {
+    sha256::Hash::hash(script.as_bytes())
+}
+
+pub fn scripthash(script: &Script) -> String {
+    format!("/scripthash/{:x}", script_hash(script))
+}
+
+pub fn scripthash_txs(script: &Script, last_seen: Option<Txid>) -> String {
+    scripthash_txs_by_hash(&script_hash(script), last_seen)
+}
+
+pub fn scripthash_txs_by_hash(script_hash: &sha256::Hash, last_seen: Option<Txid>) -> String {
+    match last_seen {
+        Some(last_seen) => format!("/scripthash/{:x}/txs/chain/{}", script_hash, last_seen),
+        None => format!("/scripthash/{:x}/txs", script_hash),
+    }
+}
+
+pub fn scripthash_txs_mempool(script: &Script) -> String {
+    format!("/scripthash/{:x}/txs/mempool", script_hash(script))
+}
+
+pub fn scripthash_utxos(script: &Script) -> String {
+    format!("/scripthash/{:x}/utxo", script_hash(script))
+}
+
+/// Path of the progress of the current difficulty adjustment period.
+#[cfg(feature = "mempool-space")]
+pub const DIFFICULTY_ADJUSTMENT: &str = "/v1/difficulty-adjustment";
+/// Path of the recommended feerates.
+#[cfg(feature = "mempool-space")]
+pub const RECOMMENDED_FEES: &str = "/v1/fees/recommended";
+
+#[cfg(feature = "elements")]
+pub fn asset(asset_id: &str) -> String {
+    format!("/asset/{asset_id}")
+}
+
+#[cfg(feature = "elements")]
+pub fn asset_txs(asset_id: &str, last_seen: Option<Txid>) -> String {
+    match last_seen {
+        Some(last_seen) => format!("/asset/{asset_id}/txs/chain/{last_seen}"),
+        None => format!("/asset/{asset_id}/txs"),
+    }
+}
+
+#[cfg(feature = "elements")]
+pub fn asset_supply(asset_id: &str) -> String {
+    format!("/asset/{asset_id}/supply")
+}